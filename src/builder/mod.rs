@@ -0,0 +1,210 @@
+use std::io::Write;
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use crate::headers::{SMXHeader, CompressionType};
+use crate::v1types::{CodeV1Header, DataHeader};
+
+// Size of the container header and of each section table entry.
+const HEADER_SIZE: usize = 24;
+const SECTION_ENTRY_SIZE: usize = 12;
+
+// Assembles a minimal .smx image, for fixtures in this crate's tests and in
+// crates built on top of it. Sections are emitted in the order they are
+// added; names referenced by the typed helpers are collected into a ".names"
+// section that is appended on build unless one was added explicitly.
+#[derive(Debug, Clone)]
+pub struct SmxBuilder {
+    version: u16,
+
+    compression: CompressionType,
+
+    sections: Vec<(String, Vec<u8>)>,
+
+    // Pending ".names" contents. Offset 0 is always the empty string.
+    names: Vec<u8>,
+}
+
+impl Default for SmxBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SmxBuilder {
+    pub fn new() -> Self {
+        Self {
+            version: SMXHeader::SP1_VERSION_1_1,
+            compression: CompressionType::CompressionNone,
+            sections: Vec::new(),
+            names: vec![0],
+        }
+    }
+
+    pub fn version(mut self, version: u16) -> Self {
+        self.version = version;
+        self
+    }
+
+    // CompressionNone or CompressionGZ; anything else is written as-is so
+    // callers can produce files the parser rejects.
+    pub fn compression(mut self, compression: CompressionType) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    // Add a section with raw contents.
+    pub fn section(mut self, name: &str, data: Vec<u8>) -> Self {
+        self.sections.push((name.to_string(), data));
+        self
+    }
+
+    // Intern a string in the pending ".names" section and return its offset.
+    pub fn add_name(&mut self, name: &str) -> i32 {
+        let offset = self.names.len() as i32;
+
+        self.names.extend_from_slice(name.as_bytes());
+        self.names.push(0);
+
+        offset
+    }
+
+    // Add a ".code" section holding |cells|, with a version 13 code header.
+    pub fn code(self, cells: &[i32]) -> Self {
+        let code_offset = CodeV1Header::SIZE + 4;
+
+        let mut data: Vec<u8> = Vec::new();
+
+        data.extend(&((cells.len() * 4) as i32).to_le_bytes());
+        data.push(4);
+        data.push(SMXHeader::CODE_VERSION_CURRENT);
+        data.extend(&0u16.to_le_bytes());
+        data.extend(&0i32.to_le_bytes());
+        data.extend(&code_offset.to_le_bytes());
+        data.extend(&0i32.to_le_bytes());
+
+        for cell in cells {
+            data.extend(&cell.to_le_bytes());
+        }
+
+        self.section(".code", data)
+    }
+
+    // Add a ".data" section with the initial contents of memory.
+    pub fn data(self, bytes: &[u8], memory_size: u32) -> Self {
+        let mut data: Vec<u8> = Vec::new();
+
+        data.extend(&(bytes.len() as u32).to_le_bytes());
+        data.extend(&memory_size.to_le_bytes());
+        data.extend(&(DataHeader::SIZE as u32).to_le_bytes());
+        data.extend_from_slice(bytes);
+
+        self.section(".data", data)
+    }
+
+    // Add a ".publics" section of (name, code address) pairs.
+    pub fn publics(mut self, publics: &[(&str, u32)]) -> Self {
+        let mut data: Vec<u8> = Vec::new();
+
+        for (name, address) in publics {
+            let offset = self.add_name(name);
+
+            data.extend(&address.to_le_bytes());
+            data.extend(&offset.to_le_bytes());
+        }
+
+        self.section(".publics", data)
+    }
+
+    // Add a ".pubvars" section of (name, data address) pairs.
+    pub fn pubvars(mut self, pubvars: &[(&str, u32)]) -> Self {
+        let mut data: Vec<u8> = Vec::new();
+
+        for (name, address) in pubvars {
+            let offset = self.add_name(name);
+
+            data.extend(&address.to_le_bytes());
+            data.extend(&offset.to_le_bytes());
+        }
+
+        self.section(".pubvars", data)
+    }
+
+    // Add a ".natives" section; natives are indexed in the given order.
+    pub fn natives(mut self, natives: &[&str]) -> Self {
+        let mut data: Vec<u8> = Vec::new();
+
+        for name in natives {
+            let offset = self.add_name(name);
+
+            data.extend(&offset.to_le_bytes());
+        }
+
+        self.section(".natives", data)
+    }
+
+    // Emit the image.
+    pub fn build(&self) -> Vec<u8> {
+        let mut sections: Vec<(&str, &[u8])> = self.sections.iter().map(|(n, d)| (n.as_str(), d.as_slice())).collect();
+
+        if !self.sections.iter().any(|(name, _)| name == ".names") {
+            sections.push((".names", &self.names));
+        }
+
+        let mut string_table: Vec<u8> = Vec::new();
+        let mut name_offsets: Vec<i32> = Vec::with_capacity(sections.len());
+
+        for (name, _) in &sections {
+            name_offsets.push(string_table.len() as i32);
+
+            string_table.extend_from_slice(name.as_bytes());
+            string_table.push(0);
+        }
+
+        let string_table_offset = HEADER_SIZE + SECTION_ENTRY_SIZE * sections.len();
+        let data_offset = string_table_offset + string_table.len();
+
+        let mut table: Vec<u8> = Vec::with_capacity(string_table_offset - HEADER_SIZE);
+        let mut body: Vec<u8> = Vec::new();
+
+        for (i, (_, data)) in sections.iter().enumerate() {
+            table.extend(&name_offsets[i].to_le_bytes());
+            table.extend(&((data_offset + body.len()) as i32).to_le_bytes());
+            table.extend(&(data.len() as i32).to_le_bytes());
+
+            body.extend_from_slice(data);
+        }
+
+        let image_size = data_offset + body.len();
+
+        let (compression, body) = match self.compression {
+            CompressionType::CompressionGZ => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+
+                // Writing to a Vec cannot fail.
+                encoder.write_all(&body).unwrap();
+
+                (1u8, encoder.finish().unwrap())
+            },
+            CompressionType::CompressionNone => (0u8, body),
+            CompressionType::CompressionUnknown => (0xff, body),
+        };
+
+        let disk_size = data_offset + body.len();
+
+        let mut image: Vec<u8> = Vec::with_capacity(disk_size);
+
+        image.extend(&SMXHeader::FILE_MAGIC.to_le_bytes());
+        image.extend(&self.version.to_le_bytes());
+        image.push(compression);
+        image.extend(&(disk_size as i32).to_le_bytes());
+        image.extend(&(image_size as i32).to_le_bytes());
+        image.push(sections.len() as u8);
+        image.extend(&(string_table_offset as i32).to_le_bytes());
+        image.extend(&(data_offset as i32).to_le_bytes());
+        image.extend(table);
+        image.extend(string_table);
+        image.extend(body);
+
+        image
+    }
+}
@@ -14,6 +14,15 @@ pub enum Error {
     InvalidIndex,
     OffsetOverflow,
     SizeOverflow,
+    MissingSection(&'static str),
+    UnknownTypeCode(u8),
+    UnknownOpcode(u8),
+    UnsupportedCodeVersion(u8),
+
+    // Raised by strict parsing only.
+    UnsupportedVersion(u16),
+    UnknownSection(String),
+    InvalidEncoding,
 
     Other(&'static str),
 }
@@ -34,6 +43,13 @@ impl Display for Error {
             Error::InvalidIndex => write!(f, "Invalid index"),
             Error::OffsetOverflow => write!(f, "Offset overflow"),
             Error::SizeOverflow => write!(f, "Size overflow"),
+            Error::MissingSection(name) => write!(f, "Missing section: {}", name),
+            Error::UnknownTypeCode(code) => write!(f, "Unknown type code: {:#x}", code),
+            Error::UnknownOpcode(op) => write!(f, "Unknown opcode: {:#x}", op),
+            Error::UnsupportedCodeVersion(version) => write!(f, "Unsupported code version: {}", version),
+            Error::UnsupportedVersion(version) => write!(f, "Unsupported version: {:#06x}", version),
+            Error::UnknownSection(ref name) => write!(f, "Unknown section: {}", name),
+            Error::InvalidEncoding => write!(f, "Invalid string encoding"),
             Error::Other(msg) => write!(f, "{}", msg),
         }
     }
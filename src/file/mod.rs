@@ -1,10 +1,163 @@
 use std::rc::Rc;
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 use crate::headers::{SMXHeader, SectionEntry};
 use crate::sections::*;
 use crate::rtti::*;
-use crate::v1disassembler::V1Disassembler;
-use crate::errors::Result;
+use crate::v1types::{PublicEntry, NativeEntry, PubvarEntry, TagEntry, DebugSymbolEntry};
+use crate::v1opcodes::V1OPCode;
+use crate::v1disassembler::{V1Disassembler, V1Instruction};
+use crate::v1listing::{function_listing, format_address, DisassemblyOptions};
+use crate::errors::{Result, Error};
+
+// Globals that build tooling is known to use for embedding a unix build
+// timestamp. Extend as new conventions are encountered.
+pub const BUILD_TIMESTAMP_SYMBOLS: &[&str] = &[
+    "__BUILD_TIMESTAMP__",
+    "BUILD_TIMESTAMP",
+    "g_iBuildTimestamp",
+];
+
+// Earliest plausible build time (2004-01-01), predating SourceMod itself.
+const MIN_BUILD_TIMESTAMP: u32 = 1_072_915_200;
+
+// Natives whose first argument names a console command or convar.
+pub const COMMAND_NATIVES: &[&str] = &[
+    "RegConsoleCmd",
+    "RegAdminCmd",
+    "RegServerCmd",
+];
+
+pub const CONVAR_NATIVES: &[&str] = &[
+    "CreateConVar",
+    // new ConVar(...)
+    "ConVar.ConVar",
+];
+
+// Most arguments a traced native call is given. Argument counts come from
+// the file, so larger ones are treated as bogus rather than allocated.
+const MAX_NATIVE_ARGS: usize = 128;
+
+// A native call site along with the arguments that could be traced back to
+// constant pushes. |args[0]| is the first argument of the call.
+struct NativeCall {
+    native: i32,
+    args: Vec<Option<i32>>,
+}
+
+// Sections modeled by SMXFile. Anything else ends up in |unknown_sections|.
+// Keep in sync with the section match in SMXFile::new_with_options.
+pub const KNOWN_SECTIONS: &[&str] = &[
+    ".names",
+    ".dbg.strings",
+    ".dbg.info",
+    ".natives",
+    ".publics",
+    ".pubvars",
+    ".tags",
+    ".data",
+    ".code",
+    ".dbg.files",
+    ".dbg.lines",
+    ".dbg.natives",
+    ".dbg.symbols",
+    ".dbg.methods",
+    ".dbg.globals",
+    ".dbg.locals",
+    "rtti.data",
+    "rtti.classdefs",
+    "rtti.enumstructs",
+    "rtti.enumstruct_fields",
+    "rtti.fields",
+    "rtti.methods",
+    "rtti.natives",
+    "rtti.enums",
+    "rtti.typedefs",
+    "rtti.typesets",
+];
+
+pub fn is_known_section(name: &str) -> bool {
+    KNOWN_SECTIONS.contains(&name)
+}
+
+// Longest chain of calls reachable from a function.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CallDepth {
+    // Number of nested calls; a function that calls nothing has depth 0.
+    Bounded(usize),
+
+    // A recursive function is reachable, so there is no static bound.
+    Recursive,
+}
+
+// How an instruction touches a data address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessKind {
+    Read,
+    Write,
+
+    // INC/DEC update memory in place.
+    ReadWrite,
+}
+
+// Everything known about a single function, gathered in one place.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSummary {
+    pub name: String,
+
+    pub address: i32,
+
+    // Decoded from rtti.methods, when the plugin carries RTTI.
+    pub signature: Option<String>,
+
+    // Bytes from the PROC to the end of the last instruction.
+    pub size: u32,
+
+    pub instruction_count: usize,
+
+    // Source file and line the function starts at.
+    pub source: Option<(String, u32)>,
+
+    // Whether .dbg.methods has an entry for the function, i.e. its locals
+    // can be resolved.
+    pub has_debug_info: bool,
+}
+
+// A function known by address, either public or discovered through a CALL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionInfo {
+    pub address: u32,
+
+    pub name: String,
+
+    pub is_public: bool,
+}
+
+// The metadata a plugin publishes through its "myinfo" pubvar.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PluginInfo {
+    pub name: String,
+
+    pub description: String,
+
+    pub author: String,
+
+    pub version: String,
+
+    pub url: String,
+}
+
+// Controls how strictly a file is validated while loading.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    // Reject anything the format does not strictly allow instead of working
+    // around it: out of range versions, unknown sections, overlapping or out
+    // of bounds sections, and strings that are not valid UTF-8.
+    pub strict: bool,
+}
 
 #[derive(Default)]
 pub struct SMXFile {
@@ -36,13 +189,51 @@ pub struct SMXFile {
     pub rtti_typedefs: Option<Rc<SMXRTTITypedefTable>>,
     pub rtti_typesets: Option<Rc<SMXRTTITypesetTable>>,
 
+    pub debug_natives: Option<Rc<SMXDebugNativesTable>>,
+    pub debug_symbols: Option<Rc<SMXDebugSymbolsTable>>,
     pub debug_methods: Option<Rc<SMXDebugMethods>>,
     pub debug_globals: Option<Rc<RefCell<SMXDebugGlobals>>>,
     pub debug_locals: Option<Rc<SMXDebugLocals>>,
+
+    // Disassembled functions, keyed by their PROC address.
+    pub functions: HashMap<i32, Vec<V1Instruction>>,
+
+    // Names of publics, called functions and legacy .dbg.symbols functions
+    // by address. Publics take precedence, then debug symbols.
+    function_names: HashMap<u32, String>,
+
+    // Addresses of publics and called functions, i.e. functions that are or
+    // will be disassembled.
+    discovered: HashSet<u32>,
+
+    xrefs: RefCell<Option<HashMap<i32, Vec<i32>>>>,
+
+    function_starts: RefCell<Vec<i32>>,
 }
 
 impl SMXFile {
     pub fn new<T>(data: T) -> Result<Rc<RefCell<SMXFile>>>
+    where
+        T: AsRef<[u8]>,
+    {
+        SMXFile::new_with_options(data, ParseOptions::default())
+    }
+
+    // Read a whole file from |reader| and parse it.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Rc<RefCell<SMXFile>>> {
+        let mut data: Vec<u8> = Vec::new();
+
+        reader.read_to_end(&mut data)?;
+
+        SMXFile::new(data)
+    }
+
+    // Read and parse the plugin at |path|.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Rc<RefCell<SMXFile>>> {
+        SMXFile::from_reader(File::open(path)?)
+    }
+
+    pub fn new_with_options<T>(data: T, options: ParseOptions) -> Result<Rc<RefCell<SMXFile>>>
     where
         T: AsRef<[u8]>,
     {
@@ -52,15 +243,19 @@ impl SMXFile {
             {
                 let file_mut = &mut *file.borrow_mut();
 
-                file_mut.header = Rc::new(SMXHeader::new(&data)?);
+                file_mut.header = Rc::new(SMXHeader::new_lenient(&data)?);
+
+                if options.strict {
+                    SMXFile::validate_header(&file_mut.header)?;
+                }
                 file_mut.unknown_sections = Vec::new();
                 file_mut.called_functions = Some(Rc::new(RefCell::new(SMXCalledFunctionsTable::new())));
 
                 for section in &file_mut.header.sections {
                     match section.name.as_ref() {
-                        ".names"  => file_mut.names = Some(Rc::new(RefCell::new(SMXNameTable::new(Rc::clone(&file_mut.header), Rc::clone(&section))))),
-                        ".dbg.strings" => file_mut.debug_names = Some(Rc::new(RefCell::new(SMXNameTable::new(Rc::clone(&file_mut.header), Rc::clone(&section))))),
-                        ".dbg.info" => file_mut.debug_info = Some(Rc::new(SMXDebugInfoSection::new(Rc::clone(&file_mut.header), Rc::clone(&section))?)),
+                        ".names"  => file_mut.names = Some(Rc::new(RefCell::new(SMXNameTable::new(Rc::clone(&file_mut.header), Rc::clone(section))))),
+                        ".dbg.strings" => file_mut.debug_names = Some(Rc::new(RefCell::new(SMXNameTable::new(Rc::clone(&file_mut.header), Rc::clone(section))))),
+                        ".dbg.info" => file_mut.debug_info = Some(Rc::new(SMXDebugInfoSection::new(Rc::clone(&file_mut.header), Rc::clone(section))?)),
                         _ => (),
                     }
                 }
@@ -73,44 +268,90 @@ impl SMXFile {
                 for section in &file_mut.header.sections {
                     match section.name.as_ref() {
                         ".names" | ".dbg.strings" | ".dbg.info" => (),
-                        ".natives" => file_mut.natives = Some(Rc::new(SMXNativeTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        ".publics" => file_mut.publics = Some(Rc::new(SMXPublicTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        ".pubvars" => file_mut.pubvars = Some(Rc::new(SMXPubvarTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        ".tags" => file_mut.tags = Some(Rc::new(SMXTagTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        ".data" => file_mut.data = Some(Rc::new(SMXDataSection::new(Rc::clone(&file_mut.header), Rc::clone(&section))?)),
-                        ".code" => file_mut.codev1 = Some(Rc::new(SMXCodeV1Section::new(Rc::clone(&file_mut.header), Rc::clone(&section))?)),
-                        ".dbg.files" => file_mut.debug_files = Some(Rc::new(SMXDebugFilesTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        ".dbg.lines" => file_mut.debug_lines = Some(Rc::new(SMXDebugLinesTable::new(Rc::clone(&file_mut.header), Rc::clone(&section))?)),
-                        // .dbg.natives and .dbg.symbols is unimplemented due to being legacy
-                        ".dbg.methods" => file_mut.debug_methods = Some(Rc::new(SMXDebugMethods::new(Rc::clone(&file_mut.header), Rc::clone(&section))?)), // names param is excluded as it's not used
-                        ".dbg.globals" => file_mut.debug_globals = Some(Rc::new(RefCell::new(SMXDebugGlobals::new(Rc::clone(&file_mut.header), Rc::clone(&section))?))),
-                        ".dbg.locals" => file_mut.debug_locals = Some(Rc::new(SMXDebugLocals::new(Rc::clone(&file), Rc::clone(&file_mut.header), Rc::clone(&section))?)),
-                        "rtti.data" => file_mut.rtti_data = Some(Rc::new(SMXRTTIData::new(Rc::clone(&file), Rc::clone(&file_mut.header), Rc::clone(&section)))),
-                        "rtti.classdefs" => file_mut.rtti_classdefs = Some(Rc::new(SMXRTTIClassDefTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.enumstructs" => file_mut.rtti_enum_structs = Some(Rc::new(SMXRTTIEnumStructTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.enumstruct_fields" => file_mut.rtti_enum_struct_fields = Some(Rc::new(SMXRTTIEnumStructFieldTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.fields" => file_mut.rtti_fields = Some(Rc::new(SMXRTTIFieldTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.methods" => file_mut.rtti_methods = Some(Rc::new(SMXRTTIMethodTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.natives" => file_mut.rtti_natives = Some(Rc::new(SMXRTTINativeTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.enums" => file_mut.rtti_enums = Some(Rc::new(SMXRTTIEnumTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.typedefs" => file_mut.rtti_typedefs = Some(Rc::new(SMXRTTITypedefTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        "rtti.typesets" => file_mut.rtti_typesets = Some(Rc::new(SMXRTTITypesetTable::new(Rc::clone(&file_mut.header), Rc::clone(&section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
-                        _ =>  file_mut.unknown_sections.push(Rc::clone(&section)),
+                        ".natives" => file_mut.natives = Some(Rc::new(SMXNativeTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        ".publics" => file_mut.publics = Some(Rc::new(SMXPublicTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        ".pubvars" => file_mut.pubvars = Some(Rc::new(SMXPubvarTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        ".tags" => file_mut.tags = Some(Rc::new(SMXTagTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        ".data" => file_mut.data = Some(Rc::new(SMXDataSection::new(Rc::clone(&file_mut.header), Rc::clone(section))?)),
+                        ".code" => file_mut.codev1 = Some(Rc::new(SMXCodeV1Section::new(Rc::clone(&file_mut.header), Rc::clone(section))?)),
+                        ".dbg.files" => file_mut.debug_files = Some(Rc::new(SMXDebugFilesTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        ".dbg.lines" => file_mut.debug_lines = Some(Rc::new(SMXDebugLinesTable::new(Rc::clone(&file_mut.header), Rc::clone(section))?)),
+                        ".dbg.natives" => file_mut.debug_natives = Some(Rc::new(SMXDebugNativesTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.debug_names.as_ref().unwrap()))?)),
+                        ".dbg.symbols" => {
+                            let count: Option<i32> = file_mut.debug_info.as_ref().map(|info| info.symbol_count());
+
+                            file_mut.debug_symbols = Some(Rc::new(SMXDebugSymbolsTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.debug_names.as_ref().unwrap()), count)?))
+                        },
+                        ".dbg.methods" => file_mut.debug_methods = Some(Rc::new(SMXDebugMethods::new(Rc::clone(&file_mut.header), Rc::clone(section))?)), // names param is excluded as it's not used
+                        ".dbg.globals" => file_mut.debug_globals = Some(Rc::new(RefCell::new(SMXDebugGlobals::new(Rc::clone(&file_mut.header), Rc::clone(section))?))),
+                        ".dbg.locals" => file_mut.debug_locals = Some(Rc::new(SMXDebugLocals::new(Rc::clone(&file), Rc::clone(&file_mut.header), Rc::clone(section))?)),
+                        "rtti.data" => file_mut.rtti_data = Some(Rc::new(SMXRTTIData::new(Rc::clone(&file), Rc::clone(&file_mut.header), Rc::clone(section)))),
+                        "rtti.classdefs" => file_mut.rtti_classdefs = Some(Rc::new(SMXRTTIClassDefTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.enumstructs" => file_mut.rtti_enum_structs = Some(Rc::new(SMXRTTIEnumStructTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.enumstruct_fields" => file_mut.rtti_enum_struct_fields = Some(Rc::new(SMXRTTIEnumStructFieldTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.fields" => file_mut.rtti_fields = Some(Rc::new(SMXRTTIFieldTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.methods" => file_mut.rtti_methods = Some(Rc::new(SMXRTTIMethodTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.natives" => file_mut.rtti_natives = Some(Rc::new(SMXRTTINativeTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.enums" => file_mut.rtti_enums = Some(Rc::new(SMXRTTIEnumTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.typedefs" => file_mut.rtti_typedefs = Some(Rc::new(SMXRTTITypedefTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        "rtti.typesets" => file_mut.rtti_typesets = Some(Rc::new(SMXRTTITypesetTable::new(Rc::clone(&file_mut.header), Rc::clone(section), Rc::clone(file_mut.names.as_ref().unwrap()))?)),
+                        _ => {
+                            debug_assert!(!is_known_section(&section.name));
+
+                            file_mut.unknown_sections.push(Rc::clone(section))
+                        },
+                    }
+                }
+
+                if options.strict {
+                    if let Some(section) = file_mut.unknown_sections.first() {
+                        return Err(Error::UnknownSection(section.name.clone()))
                     }
                 }
-            }
 
-            // Legacy debug symbols table is skipped
+                if let Some(publics) = &file_mut.publics {
+                    for pubfun in publics.entries_ref() {
+                        file_mut.function_names.entry(pubfun.address).or_insert_with(|| pubfun.name.clone());
+                        file_mut.discovered.insert(pubfun.address);
+                    }
+                }
 
-            if file.borrow_mut().publics.is_some() {
-                for pubfun in file.borrow().publics.as_ref().unwrap().entries_ref() {
-                    V1Disassembler::diassemble(Rc::clone(&file), file.borrow().header.data.clone(), Rc::clone(file.borrow().codev1.as_ref().unwrap()), pubfun.address as i32)?;
+                // Legacy plugins name their non-public functions here. They
+                // are still discovered through calls like any other.
+                if let Some(symbols) = &file_mut.debug_symbols {
+                    for sym in symbols.entries_ref() {
+                        if sym.ident == DebugSymbolEntry::IDENT_FUNCTION {
+                            file_mut.function_names.entry(sym.address as u32).or_insert_with(|| sym.name.clone());
+                        }
+                    }
                 }
             }
 
-            if file.borrow().called_functions.is_some() {
-                for fun in file.borrow().called_functions.as_ref().unwrap().borrow().entries_ref() {
-                    V1Disassembler::diassemble(Rc::clone(&file), file.borrow().header.data.clone(), Rc::clone(file.borrow().codev1.as_ref().unwrap()), fun.address as i32)?;
+            let code = file.borrow().codev1.clone();
+
+            if let Some(code) = code {
+                let publics: Vec<i32> = match &file.borrow().publics {
+                    Some(publics) => publics.entries_ref().iter().map(|p| p.address as i32).collect(),
+                    None => Vec::new(),
+                };
+
+                for addr in publics {
+                    SMXFile::disassemble_into(&file, &code, addr)?;
+                }
+
+                // Disassembly may discover further functions, so walk the
+                // called functions table until it stops growing.
+                let mut index: usize = 0;
+
+                loop {
+                    let next: Option<i32> = file.borrow().called_functions.as_ref().unwrap().borrow().entries_ref().get(index).map(|f| f.address as i32);
+
+                    match next {
+                        Some(addr) => SMXFile::disassemble_into(&file, &code, addr)?,
+                        None => break,
+                    }
+
+                    index += 1;
                 }
             }
         }
@@ -118,69 +359,1280 @@ impl SMXFile {
         Ok(file)
     }
 
-    pub fn find_global_name(&mut self, addr: i32) -> Option<String> {
-        if self.debug_globals.is_some() {
-            let sym = self.debug_globals.as_mut().unwrap().borrow_mut().find_global(addr);
+    // Header level checks for strict parsing, run before any section is read.
+    fn validate_header(header: &SMXHeader) -> Result<()> {
+        header.check_version()?;
+
+        let mut ranges: Vec<(i32, i32)> = Vec::with_capacity(header.sections.len());
+
+        for section in &header.sections {
+            let end = match section.data_offset.checked_add(section.size) {
+                Some(end) if end as usize <= header.data.len() => end,
+                _ => return Err(Error::SizeOverflow),
+            };
+
+            // Section names are stored as lossy strings, so check the raw bytes.
+            let name = &header.data[(header.string_table_offset + section.name_offset) as usize..];
+            let len = name.iter().position(|b| *b == 0).unwrap_or(name.len());
+
+            if std::str::from_utf8(&name[..len]).is_err() {
+                return Err(Error::InvalidEncoding)
+            }
+
+            // Code and data are streams of cells. Compilers do not align
+            // data_offset itself (it follows the string table), so only the
+            // size is checked.
+            let is_cell_stream = section.name == ".code" || section.name == ".data";
+
+            if is_cell_stream && section.size % 4 != 0 {
+                return Err(Error::InvalidSize)
+            }
+
+            // Name tables are validated whole; NUL separators are valid UTF-8.
+            let is_name_table = section.name == ".names" || section.name == ".dbg.strings";
+
+            if is_name_table && std::str::from_utf8(&header.data[section.data_offset as usize..end as usize]).is_err() {
+                return Err(Error::InvalidEncoding)
+            }
+
+            ranges.push((section.data_offset, end));
+        }
+
+        ranges.sort();
+
+        for pair in ranges.windows(2) {
+            if pair[1].0 < pair[0].1 {
+                return Err(Error::InvalidOffset)
+            }
+        }
+
+        Ok(())
+    }
+
+    fn disassemble_into(file: &Rc<RefCell<SMXFile>>, code: &Rc<SMXCodeV1Section>, addr: i32) -> Result<()> {
+        if file.borrow().functions.contains_key(&addr) {
+            return Ok(())
+        }
+
+        let data: Vec<u8> = file.borrow().header.data.clone();
+
+        let insns: Vec<V1Instruction> = V1Disassembler::diassemble(Rc::clone(file), data, Rc::clone(code), addr)?;
+
+        file.borrow_mut().functions.insert(addr, insns);
+
+        Ok(())
+    }
+
+    // Instructions of the function whose PROC is at |addr|, as disassembled
+    // during load.
+    pub fn instructions_at(&self, addr: i32) -> Option<&[V1Instruction]> {
+        self.functions.get(&addr).map(|insns| insns.as_slice())
+    }
+
+    // Instructions of the public function |name|. Publics are disassembled
+    // during load, so this normally returns the stored copy.
+    pub fn disassemble_function_by_name(&self, name: &str) -> Result<Vec<V1Instruction>> {
+        let addr = match &self.publics {
+            Some(publics) => publics.entries_ref().iter().find(|p| p.name == name).map(|p| p.address as i32),
+            None => None,
+        };
+
+        let addr = addr.ok_or(Error::InvalidIndex)?;
+
+        if let Some(insns) = self.functions.get(&addr) {
+            return Ok(insns.clone())
+        }
+
+        match &self.codev1 {
+            Some(code) => V1Disassembler::disassemble_bytes(&code.get_data_vec(), addr),
+            None => Err(Error::MissingSection(".code")),
+        }
+    }
+
+    // Instructions of the entry point named by the code header's main_offset.
+    // An offset of -1 means the plugin has no entry point.
+    pub fn main_function(&self) -> Result<Vec<V1Instruction>> {
+        let code = self.codev1.as_ref().ok_or(Error::MissingSection(".code"))?;
+
+        let addr = code.header().main_offset;
+
+        if addr == -1 {
+            return Err(Error::Other("No main function"))
+        }
+
+        if let Some(insns) = self.functions.get(&addr) {
+            return Ok(insns.clone())
+        }
+
+        V1Disassembler::disassemble_bytes(&code.get_data_vec(), addr)
+    }
+
+    // Section entries in the order their contents appear in the image.
+    pub fn sections_by_offset(&self) -> Vec<SectionEntry> {
+        let mut sections: Vec<SectionEntry> = self.header.sections.iter().map(|section| (**section).clone()).collect();
+
+        sections.sort_by_key(|section| (section.data_offset, section.size));
+
+        sections
+    }
+
+    // Byte ranges [start, end) between the start of section data and the end
+    // of the image that no section covers.
+    pub fn gaps_between_sections(&self) -> Vec<(i32, i32)> {
+        let mut gaps: Vec<(i32, i32)> = Vec::new();
+        let mut cursor: i32 = self.header.data_offset;
+
+        for section in self.sections_by_offset() {
+            if section.data_offset > cursor {
+                gaps.push((cursor, section.data_offset));
+            }
+
+            cursor = cursor.max(section.data_offset.saturating_add(section.size));
+        }
+
+        let image_end = self.header.data.len() as i32;
+
+        if image_end > cursor {
+            gaps.push((cursor, image_end));
+        }
+
+        gaps
+    }
+
+    // Legacy plugins without .dbg.globals fall back to .dbg.symbols.
+    pub fn find_global_name(&self, addr: i32) -> Option<String> {
+        if let Some(globals) = &self.debug_globals {
+            let sym = globals.borrow_mut().find_global(addr);
 
             if let Some(symsome) = sym {
-                return Some(self.names.as_mut().unwrap().borrow_mut().string_at(symsome.name_offset).unwrap());
+                return Some(self.names.as_ref().unwrap().borrow_mut().string_at(symsome.name_offset).unwrap());
             }
+        } else if let Some(symbols) = &self.debug_symbols {
+            return symbols.find_global(addr).map(|sym| sym.name);
         }
 
         None
     }
 
-    pub fn find_local_name(&mut self, code_addr: i32, addr: i32) -> Option<String> {
-        if self.debug_locals.is_some() {
-            let entry = self.debug_locals.as_ref().unwrap().find_local(code_addr, addr);
+    // Legacy plugins without .dbg.locals fall back to .dbg.symbols.
+    pub fn find_local_name(&self, code_addr: i32, addr: i32) -> Option<String> {
+        if let Some(locals) = &self.debug_locals {
+            let entry = locals.find_local(code_addr, addr);
 
             if let Some(entrysome) = entry {
-                return Some(self.names.as_mut().unwrap().borrow_mut().string_at(entrysome.name_offset).unwrap());
+                return Some(self.names.as_ref().unwrap().borrow_mut().string_at(entrysome.name_offset).unwrap());
             }
+        } else if let Some(symbols) = &self.debug_symbols {
+            return symbols.find_local(code_addr, addr).map(|sym| sym.name);
         }
 
         None
     }
 
-    pub fn find_function_name(&self, addr: i32) -> String {
-        if self.publics.is_some() {
-            for pubfun in self.publics.as_ref().unwrap().entries_ref() {
-                if pubfun.address == addr as u32 {
-                    return pubfun.name.clone();
+    // Declared type of the global variable at |addr|, from the debug symbols.
+    // None when there is no symbol or no type info to resolve it with.
+    pub fn find_global_type(&self, addr: i32) -> Result<Option<String>> {
+        let sym = match &self.debug_globals {
+            Some(globals) => globals.borrow_mut().find_global(addr),
+            None => None,
+        };
+
+        match (sym, &self.rtti_data) {
+            (Some(sym), Some(rtti_data)) => Ok(Some(rtti_data.type_from_id(sym.type_id)?)),
+            _ => Ok(None),
+        }
+    }
+
+    // Declared type of the local variable at stack offset |addr|.
+    pub fn find_local_type(&self, code_addr: i32, addr: i32) -> Result<Option<String>> {
+        let sym = match &self.debug_locals {
+            Some(locals) => locals.find_local(code_addr, addr),
+            None => None,
+        };
+
+        match (sym, &self.rtti_data) {
+            (Some(sym), Some(rtti_data)) => Ok(Some(rtti_data.type_from_id(sym.type_id)?)),
+            _ => Ok(None),
+        }
+    }
+
+    // Map a code address to the source file and line it was compiled from.
+    pub fn source_location(&self, code_addr: u32) -> Option<(String, u32)> {
+        let file = self.debug_files.as_ref()?.find_file(code_addr)?;
+        let line = self.debug_lines.as_ref()?.find_line(code_addr)?;
+
+        Some((file, line))
+    }
+
+    // Every public and called function once, in address order. Where both
+    // tables list an address, the public wins.
+    pub fn functions(&self) -> Vec<FunctionInfo> {
+        let mut functions: Vec<FunctionInfo> = Vec::new();
+        let mut seen: HashSet<u32> = HashSet::new();
+
+        if let Some(publics) = &self.publics {
+            for pubfun in publics.entries_ref() {
+                if seen.insert(pubfun.address) {
+                    functions.push(FunctionInfo {
+                        address: pubfun.address,
+                        name: pubfun.name.clone(),
+                        is_public: true,
+                    });
+                }
+            }
+        }
+
+        if let Some(called_functions) = &self.called_functions {
+            for fun in called_functions.borrow().entries_ref() {
+                if seen.insert(fun.address) {
+                    functions.push(FunctionInfo {
+                        address: fun.address,
+                        name: fun.name.clone(),
+                        is_public: false,
+                    });
+                }
+            }
+        }
+
+        functions.sort_by_key(|fun| fun.address);
+
+        functions
+    }
+
+    // Name of the public or called function starting at |addr|.
+    pub fn find_function_name(&self, addr: i32) -> Option<String> {
+        self.function_names.get(&(addr as u32)).cloned()
+    }
+
+    // Resolve a function name to its address, checking publics first and
+    // then the functions discovered through calls.
+    fn find_function_address(&self, name: &str) -> Option<i32> {
+        if let Some(publics) = &self.publics {
+            for pubfun in publics.entries_ref() {
+                if pubfun.name == name {
+                    return Some(pubfun.address as i32);
                 }
             }
         }
 
-        if self.called_functions.is_some() {
-            for fun in self.called_functions.as_ref().unwrap().borrow().entries_ref() {
-                if fun.address == addr as u32 {
-                    return fun.name.clone();
+        if let Some(called_functions) = &self.called_functions {
+            for fun in called_functions.borrow().entries_ref() {
+                if fun.name == name {
+                    return Some(fun.address as i32);
                 }
             }
         }
 
-        "unknown".into()
+        None
     }
 
     pub fn is_function_at_address(&self, addr: i32) -> bool {
-        // Legacy debug symbols is unimplemented
+        self.function_names.contains_key(&(addr as u32))
+    }
+
+    // Whether the function at |addr| is a public or was already discovered
+    // through a CALL.
+    pub(crate) fn is_discovered_function(&self, addr: i32) -> bool {
+        self.discovered.contains(&(addr as u32))
+    }
+
+    // Record a function discovered through a CALL.
+    pub(crate) fn add_called_function(&mut self, addr: u32) {
+        if let Some(called_functions) = &self.called_functions {
+            self.discovered.insert(addr);
 
-        if self.publics.is_some() {
-            for pubfun in self.publics.as_ref().unwrap().entries_ref() {
-                if pubfun.address == addr as u32 {
-                    return true;
+            called_functions.borrow_mut().add_function(addr);
+
+            let name = called_functions.borrow().entries_ref().last().unwrap().name.clone();
+
+            self.function_names.entry(addr).or_insert(name);
+        }
+    }
+
+    // Check that every public's address lands on a PROC opcode in the code
+    // section. Publics that cannot be read are reported as invalid.
+    pub fn verify_publics(&self) -> Vec<(PublicEntry, bool)> {
+        let mut results: Vec<(PublicEntry, bool)> = Vec::new();
+
+        if let Some(publics) = &self.publics {
+            for pubfun in publics.entries_ref() {
+                let valid = match &self.codev1 {
+                    Some(code) => code.read_cell(pubfun.address as i32).map(|op| op == V1OPCode::PROC as i32).unwrap_or(false),
+                    None => false,
+                };
+
+                results.push((pubfun.clone(), valid));
+            }
+        }
+
+        results
+    }
+
+    // Return the addresses of all instructions referencing |addr|, either as
+    // a jump or call target or as a code/data operand. The index is built on
+    // first use and cached.
+    pub fn xrefs_to(&self, addr: i32) -> Vec<i32> {
+        if self.xrefs.borrow().is_none() {
+            let mut index: HashMap<i32, Vec<i32>> = HashMap::new();
+
+            for insns in self.functions.values() {
+                for insn in insns {
+                    for target in insn.references() {
+                        index.entry(target).or_default().push(insn.address);
+                    }
                 }
             }
+
+            for sources in index.values_mut() {
+                sources.sort();
+                sources.dedup();
+            }
+
+            *self.xrefs.borrow_mut() = Some(index);
+        }
+
+        match self.xrefs.borrow().as_ref().unwrap().get(&addr) {
+            Some(sources) => sources.clone(),
+            None => Vec::new(),
         }
+    }
+
+    // Every instruction operand addressing the data section, as
+    // (code address, data address, access kind), ordered by code address.
+    pub fn data_accesses(&self) -> Vec<(i32, i32, AccessKind)> {
+        let mut accesses: Vec<(i32, i32, AccessKind)> = Vec::new();
+
+        let layout = match &self.data {
+            Some(data) => data.memory_layout(),
+            None => return accesses,
+        };
+
+        for insns in self.functions.values() {
+            for insn in insns {
+                let kind = match insn.info.opcode {
+                    V1OPCode::STOR_PRI | V1OPCode::STOR_ALT | V1OPCode::CONST | V1OPCode::ZERO | V1OPCode::REBASE => AccessKind::Write,
+                    V1OPCode::INC | V1OPCode::DEC => AccessKind::ReadWrite,
+                    _ => AccessKind::Read,
+                };
+
+                for (i, addr) in insn.params.iter().enumerate() {
+                    let in_data = *addr >= layout.data_start as i32 && (*addr as u32) < layout.data_end;
 
-        if self.called_functions.is_some() {
-            for fun in self.called_functions.as_ref().unwrap().borrow().entries_ref() {
-                if fun.address == addr as u32 {
-                    return true;
+                    if in_data && insn.is_data_operand(i) {
+                        accesses.push((insn.address, *addr, kind));
+                    }
                 }
             }
         }
 
-        false
+        accesses.sort_by_key(|access| (access.0, access.1));
+
+        accesses
+    }
+
+    // Map each disassembled function to the functions it CALLs, sorted and
+    // without duplicates. Native calls are not part of the graph.
+    pub fn call_graph(&self) -> HashMap<i32, Vec<i32>> {
+        let mut graph: HashMap<i32, Vec<i32>> = HashMap::with_capacity(self.functions.len());
+
+        for (addr, insns) in &self.functions {
+            let mut callees: Vec<i32> = insns.iter()
+                .filter(|insn| insn.info.opcode == V1OPCode::CALL)
+                .map(|insn| insn.params[0])
+                .collect();
+
+            callees.sort();
+            callees.dedup();
+
+            graph.insert(*addr, callees);
+        }
+
+        graph
+    }
+
+    // Functions that are part of a call cycle, either calling themselves or
+    // mutually recursive with others, in address order.
+    pub fn recursive_functions(&self) -> Vec<i32> {
+        let graph = self.call_graph();
+
+        let mut recursive: Vec<i32> = Vec::new();
+
+        for component in strongly_connected_components(&graph) {
+            let is_cycle = component.len() > 1 || graph[&component[0]].contains(&component[0]);
+
+            if is_cycle {
+                recursive.extend(component);
+            }
+        }
+
+        recursive.sort();
+
+        recursive
+    }
+
+    // Functions known to exist that no chain of CALLs from a public or from
+    // main reaches, in address order. Besides disassembled functions, the
+    // RTTI method table and debug symbols name functions that are only ever
+    // referenced indirectly, such as callbacks, or not at all.
+    pub fn unreachable_functions(&self) -> Vec<u32> {
+        let graph = self.call_graph();
+
+        let mut known: HashSet<i32> = graph.keys().copied().collect();
+
+        if let Some(methods) = &self.rtti_methods {
+            known.extend(methods.methods_ref().iter().map(|method| method.pcode_start));
+        }
+
+        if let Some(symbols) = &self.debug_symbols {
+            known.extend(symbols.entries_ref().iter().filter(|sym| sym.ident == DebugSymbolEntry::IDENT_FUNCTION).map(|sym| sym.address));
+        }
+
+        let mut pending: Vec<i32> = match &self.publics {
+            Some(publics) => publics.entries_ref().iter().map(|p| p.address as i32).collect(),
+            None => Vec::new(),
+        };
+
+        if let Some(code) = &self.codev1 {
+            let main = code.header().main_offset;
+
+            if code.read_cell(main).is_ok_and(|op| op == V1OPCode::PROC as i32) {
+                pending.push(main);
+            }
+        }
+
+        // Cycles are cut by only expanding each function once.
+        let mut reached: HashSet<i32> = HashSet::new();
+
+        while let Some(addr) = pending.pop() {
+            if !reached.insert(addr) {
+                continue;
+            }
+
+            if let Some(callees) = graph.get(&addr) {
+                pending.extend(callees);
+            }
+        }
+
+        let mut unreachable: Vec<u32> = known.difference(&reached).map(|addr| *addr as u32).collect();
+
+        unreachable.sort_unstable();
+
+        unreachable
+    }
+
+    // Render the call graph in graphviz DOT. Functions are keyed by address
+    // and natives by index so node ids stay valid identifiers; names only
+    // appear in the escaped labels. Native calls are drawn dashed.
+    pub fn call_graph_dot(&self) -> String {
+        let escape = |name: &str| name.replace('\\', "\\\\").replace('"', "\\\"");
+
+        let graph = self.call_graph();
+
+        let mut addrs: Vec<&i32> = graph.keys().collect();
+        addrs.sort();
+
+        let mut natives: Vec<(i32, i32)> = Vec::new();
+
+        for addr in &addrs {
+            for insn in &self.functions[addr] {
+                if insn.info.opcode == V1OPCode::SYSREQ_C || insn.info.opcode == V1OPCode::SYSREQ_N {
+                    natives.push((**addr, insn.params[0]));
+                }
+            }
+        }
+
+        natives.sort();
+        natives.dedup();
+
+        let mut native_nodes: Vec<i32> = natives.iter().map(|(_, native)| *native).collect();
+        native_nodes.sort();
+        native_nodes.dedup();
+
+        let mut dot: String = String::from("digraph calls {\n");
+
+        for addr in &addrs {
+            dot += &format!("    f_{:x} [label=\"{}\"];\n", addr, escape(&self.find_function_name(**addr).unwrap_or_else(|| format!("sub_{:x}", addr))));
+        }
+
+        for native in &native_nodes {
+            let name: String = match &self.natives {
+                Some(table) if *native >= 0 && (*native as usize) < table.size() => table.get_entry(*native as usize).name,
+                _ => format!("native_{}", native),
+            };
+
+            dot += &format!("    n_{} [label=\"{}\", shape=box];\n", native, escape(&name));
+        }
+
+        for addr in &addrs {
+            for callee in &graph[addr] {
+                dot += &format!("    f_{:x} -> f_{:x};\n", addr, callee);
+            }
+        }
+
+        for (addr, native) in &natives {
+            dot += &format!("    f_{:x} -> n_{} [style=dashed];\n", addr, native);
+        }
+
+        dot += "}\n";
+
+        dot
+    }
+
+    // Functions that call no other plugin function, in address order. With
+    // |count_natives| set, a native call also disqualifies a function.
+    pub fn leaf_functions(&self, count_natives: bool) -> Vec<i32> {
+        let mut leaves: Vec<i32> = Vec::new();
+
+        for (addr, insns) in &self.functions {
+            let calls = insns.iter().any(|insn| match insn.info.opcode {
+                V1OPCode::CALL => true,
+                V1OPCode::SYSREQ_C | V1OPCode::SYSREQ_N => count_natives,
+                _ => false,
+            });
+
+            if !calls {
+                leaves.push(*addr);
+            }
+        }
+
+        leaves.sort();
+
+        leaves
     }
-}
\ No newline at end of file
+
+    // Compute how deeply calls can nest starting from the named function.
+    pub fn max_call_depth(&self, name: &str) -> Result<CallDepth> {
+        let addr = self.find_function_address(name).ok_or(Error::InvalidIndex)?;
+
+        let graph = self.call_graph();
+        let recursive: HashSet<i32> = self.recursive_functions().into_iter().collect();
+
+        fn depth(addr: i32, graph: &HashMap<i32, Vec<i32>>, recursive: &HashSet<i32>, memo: &mut HashMap<i32, CallDepth>) -> CallDepth {
+            if recursive.contains(&addr) {
+                return CallDepth::Recursive
+            }
+
+            if let Some(known) = memo.get(&addr) {
+                return *known
+            }
+
+            let mut deepest: CallDepth = CallDepth::Bounded(0);
+
+            if let Some(callees) = graph.get(&addr) {
+                for callee in callees {
+                    match depth(*callee, graph, recursive, memo) {
+                        CallDepth::Recursive => {
+                            deepest = CallDepth::Recursive;
+                            break;
+                        },
+                        CallDepth::Bounded(n) => {
+                            if let CallDepth::Bounded(d) = deepest {
+                                deepest = CallDepth::Bounded(d.max(n + 1));
+                            }
+                        },
+                    }
+                }
+            }
+
+            memo.insert(addr, deepest);
+
+            deepest
+        }
+
+        Ok(depth(addr, &graph, &recursive, &mut HashMap::new()))
+    }
+
+    // Best-effort lookup of an embedded build timestamp. The compiler does not
+    // record one, so this looks for a public or debug global named in
+    // |BUILD_TIMESTAMP_SYMBOLS| whose value is a plausible unix time.
+    pub fn build_timestamp(&self) -> Option<u32> {
+        let data = self.data.as_ref()?;
+
+        let mut candidates: Vec<u32> = Vec::new();
+
+        if let Some(pubvars) = &self.pubvars {
+            for var in pubvars.entries() {
+                if BUILD_TIMESTAMP_SYMBOLS.contains(&var.name.as_str()) {
+                    candidates.push(var.address);
+                }
+            }
+        }
+
+        if let (Some(globals), Some(names)) = (&self.debug_globals, &self.names) {
+            for sym in globals.borrow().symbol_entries() {
+                if let Ok(name) = names.borrow_mut().string_at(sym.name_offset) {
+                    if BUILD_TIMESTAMP_SYMBOLS.contains(&name.as_str()) {
+                        candidates.push(sym.address as u32);
+                    }
+                }
+            }
+        }
+
+        for addr in candidates {
+            if let Ok(value) = data.read_cell(addr) {
+                if value as u32 >= MIN_BUILD_TIMESTAMP {
+                    return Some(value as u32);
+                }
+            }
+        }
+
+        None
+    }
+
+    // Recover native call sites from the disassembly. Arguments are traced by
+    // replaying pushes within a function: constant pushes (and a PRI pushed
+    // right after CONST_PRI) are known, anything else is recorded as None.
+    // The push stack is discarded at every CALL.
+    fn native_calls(&self) -> Vec<NativeCall> {
+        let mut calls: Vec<NativeCall> = Vec::new();
+
+        let mut addrs: Vec<&i32> = self.functions.keys().collect();
+        addrs.sort();
+
+        for addr in addrs {
+            let mut pushed: Vec<Option<i32>> = Vec::new();
+            let mut pri: Option<i32> = None;
+
+            for insn in &self.functions[addr] {
+                match insn.info.opcode {
+                    V1OPCode::PUSH_C | V1OPCode::PUSH2_C | V1OPCode::PUSH3_C | V1OPCode::PUSH4_C | V1OPCode::PUSH5_C => {
+                        pushed.extend(insn.params.iter().map(|p| Some(*p)));
+                    },
+                    V1OPCode::PUSH_PRI => pushed.push(pri),
+                    V1OPCode::PUSH_ALT => pushed.push(None),
+                    V1OPCode::SYSREQ_C | V1OPCode::SYSREQ_N => {
+                        // SYSREQ_C takes its argument byte count from the stack.
+                        let argc: i32 = match insn.info.opcode {
+                            V1OPCode::SYSREQ_N => insn.params[1],
+                            _ => match pushed.pop() {
+                                Some(Some(bytes)) => bytes / 4,
+                                _ => 0,
+                            },
+                        };
+
+                        let argc: usize = match argc >= 0 && argc as usize <= MAX_NATIVE_ARGS {
+                            true => argc as usize,
+                            false => 0,
+                        };
+
+                        let split: usize = pushed.len().saturating_sub(argc);
+                        let mut args: Vec<Option<i32>> = pushed.split_off(split);
+                        args.reverse();
+                        args.resize(argc, None);
+
+                        calls.push(NativeCall {
+                            native: insn.params[0],
+                            args,
+                        });
+                    },
+                    V1OPCode::CALL => pushed.clear(),
+                    // Remaining pushes take stack or memory operands.
+                    _ if insn.info.name.starts_with("push") => pushed.resize(pushed.len() + insn.params.len(), None),
+                    _ => (),
+                }
+
+                pri = match insn.info.opcode {
+                    V1OPCode::CONST_PRI => Some(insn.params[0]),
+                    V1OPCode::BREAK | V1OPCode::PUSH_PRI => pri,
+                    _ => None,
+                };
+            }
+        }
+
+        calls
+    }
+
+    // Literal string arguments passed at position |arg| to the named native.
+    fn native_string_args(&self, native: &str, arg: usize) -> Vec<String> {
+        let mut strings: Vec<String> = Vec::new();
+
+        let (natives, data) = match (&self.natives, &self.data) {
+            (Some(natives), Some(data)) => (natives, data),
+            _ => return strings,
+        };
+
+        for call in self.native_calls() {
+            if call.native < 0 || call.native as usize >= natives.size() || natives.get_entry(call.native as usize).name != native {
+                continue;
+            }
+
+            if let Some(Some(addr)) = call.args.get(arg) {
+                if let Ok(s) = data.string_at(*addr as u32) {
+                    if !strings.contains(&s) {
+                        strings.push(s);
+                    }
+                }
+            }
+        }
+
+        strings
+    }
+
+    // Natives marked optional via MarkNativeAsOptional. This matches every
+    // call to that native whose name argument is a constant pointing at a
+    // string literal in .data, as emitted for extension and library includes.
+    pub fn optional_natives(&self) -> Vec<String> {
+        self.native_string_args("MarkNativeAsOptional", 0)
+    }
+
+    // Console commands registered through COMMAND_NATIVES. Like
+    // optional_natives, only names passed as string literals are found;
+    // commands whose names are built at runtime are missed.
+    pub fn registered_commands(&self) -> Vec<String> {
+        self.first_string_args(COMMAND_NATIVES)
+    }
+
+    // Convars created through CONVAR_NATIVES, with the same caveat as
+    // registered_commands.
+    pub fn registered_convars(&self) -> Vec<String> {
+        self.first_string_args(CONVAR_NATIVES)
+    }
+
+    fn first_string_args(&self, natives: &[&str]) -> Vec<String> {
+        let mut strings: Vec<String> = Vec::new();
+
+        for native in natives {
+            for s in self.native_string_args(native, 0) {
+                if !strings.contains(&s) {
+                    strings.push(s);
+                }
+            }
+        }
+
+        strings
+    }
+
+    // Natives invoked by at least one SYSREQ in the disassembly.
+    pub fn referenced_natives(&self) -> Vec<NativeEntry> {
+        let indexes: HashSet<i32> = self.referenced_native_indexes();
+
+        match &self.natives {
+            Some(natives) => natives.entries().into_iter().enumerate().filter(|(i, _)| indexes.contains(&(*i as i32))).map(|(_, n)| n).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Natives declared in the natives table but never called.
+    pub fn unused_natives(&self) -> Vec<NativeEntry> {
+        let indexes: HashSet<i32> = self.referenced_native_indexes();
+
+        match &self.natives {
+            Some(natives) => natives.entries().into_iter().enumerate().filter(|(i, _)| !indexes.contains(&(*i as i32))).map(|(_, n)| n).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn referenced_native_indexes(&self) -> HashSet<i32> {
+        let mut indexes: HashSet<i32> = HashSet::new();
+
+        for insns in self.functions.values() {
+            for insn in insns {
+                if insn.info.opcode == V1OPCode::SYSREQ_C || insn.info.opcode == V1OPCode::SYSREQ_N {
+                    indexes.insert(insn.params[0]);
+                }
+            }
+        }
+
+        indexes
+    }
+
+    // Decode the instruction starting at |addr| using the stored function
+    // disassembly. Addresses outside the code section are an error; addresses
+    // that do not start an instruction yield None.
+    pub fn instruction_at(&self, addr: i32) -> Result<Option<V1Instruction>> {
+        match &self.codev1 {
+            Some(code) if addr >= 0 && addr < code.header().code_size => (),
+            _ => return Err(Error::OffsetOverflow),
+        }
+
+        if self.function_starts.borrow().is_empty() {
+            let mut starts: Vec<i32> = self.functions.keys().cloned().collect();
+            starts.sort();
+
+            *self.function_starts.borrow_mut() = starts;
+        }
+
+        let starts = self.function_starts.borrow();
+
+        let index: usize = match starts.binary_search(&addr) {
+            Ok(i) => i,
+            Err(0) => return Ok(None),
+            Err(i) => i - 1,
+        };
+
+        let insns: &Vec<V1Instruction> = &self.functions[&starts[index]];
+
+        match insns.binary_search_by_key(&addr, |insn| insn.address) {
+            Ok(i) => Ok(Some(insns[i].clone())),
+            Err(_) => Ok(None),
+        }
+    }
+
+    // Read the current value of each public variable from the data section.
+    // Array pubvars report their first cell; the pubvar table carries no size
+    // information to tell them apart from scalars.
+    pub fn pubvar_values(&self) -> Result<Vec<(String, i32)>> {
+        let pubvars = match &self.pubvars {
+            Some(pubvars) => pubvars,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut values: Vec<(String, i32)> = Vec::with_capacity(pubvars.size());
+
+        for var in pubvars.entries() {
+            let value = self.pubvar_value(&var)?;
+
+            values.push((var.name, value));
+        }
+
+        Ok(values)
+    }
+
+    // Read the cell a public variable points at.
+    pub fn pubvar_value(&self, entry: &PubvarEntry) -> Result<i32> {
+        match &self.data {
+            Some(data) => data.read_cell(entry.address),
+            None => Err(Error::MissingSection(".data")),
+        }
+    }
+
+    // Read the cell a public variable points at as a float.
+    pub fn pubvar_float(&self, entry: &PubvarEntry) -> Result<f32> {
+        match &self.data {
+            Some(data) => data.read_float(entry.address),
+            None => Err(Error::MissingSection(".data")),
+        }
+    }
+
+    // Read the plugin's "myinfo" struct: five cells pointing at the name,
+    // description, author, version and url strings in .data.
+    pub fn plugin_info(&self) -> Option<PluginInfo> {
+        let myinfo = self.pubvars.as_ref()?.entries().into_iter().find(|var| var.name == "myinfo")?.address;
+        let data = self.data.as_ref()?;
+
+        let field = |index: u32| -> Option<String> {
+            let ptr = data.read_cell(myinfo.checked_add(4 * index)?).ok()?;
+
+            // Negative pointers wrap past the end of .data and fail the lookup.
+            data.string_at(ptr as u32).ok()
+        };
+
+        Some(PluginInfo {
+            name: field(0)?,
+            description: field(1)?,
+            author: field(2)?,
+            version: field(3)?,
+            url: field(4)?,
+        })
+    }
+
+    // The raw bytes of a function, starting at its PROC, with every operand
+    // byte masked out as None. Only opcode cells are kept, so the result stays
+    // stable across recompiles that shift addresses and constants around.
+    pub fn function_signature_bytes(&self, name: &str) -> Result<Vec<Option<u8>>> {
+        let addr = self.find_function_address(name).ok_or(Error::InvalidIndex)?;
+
+        let (code, insns) = match (&self.codev1, self.functions.get(&addr)) {
+            (Some(code), Some(insns)) => (code, insns),
+            _ => return Err(Error::InvalidIndex),
+        };
+
+        let data = code.get_data_vec();
+
+        let end = match insns.last() {
+            Some(last) => last.address + 4 * (1 + last.params.len() as i32),
+            None => addr + 4,
+        };
+
+        if end as usize > data.len() {
+            return Err(Error::OffsetOverflow)
+        }
+
+        let mut opcodes: HashSet<i32> = insns.iter().map(|insn| insn.address).collect();
+        opcodes.insert(addr);
+
+        let mut bytes: Vec<Option<u8>> = Vec::with_capacity((end - addr) as usize);
+
+        for offset in addr..end {
+            let cell = offset - (offset - addr) % 4;
+
+            bytes.push(if opcodes.contains(&cell) { Some(data[offset as usize]) } else { None });
+        }
+
+        Ok(bytes)
+    }
+
+    // Collect a function's signature, size, instruction count and source
+    // location.
+    pub fn function_summary(&self, name: &str) -> Result<FunctionSummary> {
+        let addr = self.find_function_address(name).ok_or(Error::InvalidIndex)?;
+        let insns = self.functions.get(&addr).ok_or(Error::InvalidIndex)?;
+
+        let end = match insns.last() {
+            Some(last) => last.address + 4 * (1 + last.params.len() as i32),
+            None => addr + 4,
+        };
+
+        let method = match &self.rtti_methods {
+            Some(methods) => methods.methods_ref().iter().position(|m| m.pcode_start == addr),
+            None => None,
+        };
+
+        let signature = match (method, &self.rtti_methods, &self.rtti_data) {
+            (Some(index), Some(methods), Some(rtti_data)) => Some(rtti_data.function_type_from_offset(methods.methods_ref()[index].signature)?),
+            _ => None,
+        };
+
+        let has_debug_info = match (method, &self.debug_methods) {
+            (Some(index), Some(debug_methods)) => debug_methods.entries_ref().iter().any(|m| m.method_index == index as i32),
+            _ => false,
+        };
+
+        Ok(FunctionSummary {
+            name: name.to_string(),
+            address: addr,
+            signature,
+            size: (end - addr) as u32,
+            instruction_count: insns.len(),
+            source: self.source_location(addr as u32),
+            has_debug_info,
+        })
+    }
+
+    // Listing of the function at |addr|, starting with its PROC. Lines carry
+    // the absolute address and the offset from the PROC; jump targets are
+    // labelled and string constants commented.
+    pub fn disassemble_listing(&self, addr: i32) -> Result<String> {
+        let insns = self.functions.get(&addr).ok_or(Error::InvalidIndex)?;
+
+        let options = DisassemblyOptions {
+            relative_to: Some(addr),
+            ..Default::default()
+        };
+
+        let mut listing: String = format!("{}: proc\n", format_address(addr, &options));
+
+        listing += &function_listing(self, insns, &options);
+
+        Ok(listing)
+    }
+
+    // Dump the header, section table, resolved tables and disassembly as a
+    // JSON document. Tables the plugin does not carry are left out.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String> {
+        use serde_json::{json, Map, Value};
+
+        fn to_value<T: serde::Serialize>(value: T) -> Result<Value> {
+            serde_json::to_value(value).map_err(|_| Error::Other("Failed to serialize"))
+        }
+
+        let mut doc: Map<String, Value> = Map::new();
+
+        doc.insert("header".into(), json!({
+            "magic": self.header.magic,
+            "version": self.header.version,
+            "compression": self.header.compression_type.to_string(),
+            "disk_size": self.header.disk_size,
+            "image_size": self.header.image_size,
+            "section_count": self.header.section_count,
+            "string_table_offset": self.header.string_table_offset,
+            "data_offset": self.header.data_offset,
+            "debug_packed": self.header.debug_packed,
+        }));
+
+        let sections: Vec<&SectionEntry> = self.header.sections.iter().map(|section| &**section).collect();
+        doc.insert("sections".into(), to_value(sections)?);
+
+        if let Some(publics) = &self.publics {
+            doc.insert("publics".into(), to_value(publics.entries_ref())?);
+        }
+
+        if let Some(natives) = &self.natives {
+            doc.insert("natives".into(), to_value(natives.entries())?);
+        }
+
+        if let Some(pubvars) = &self.pubvars {
+            doc.insert("pubvars".into(), to_value(pubvars.entries())?);
+        }
+
+        if let Some(tags) = &self.tags {
+            let entries: Vec<TagEntry> = tags.entries().iter().map(|tag| tag.entry()).collect();
+            doc.insert("tags".into(), to_value(entries)?);
+        }
+
+        let mut rtti: Map<String, Value> = Map::new();
+
+        if let Some(methods) = &self.rtti_methods {
+            rtti.insert("methods".into(), to_value(methods.methods_ref())?);
+        }
+
+        if let Some(natives) = &self.rtti_natives {
+            rtti.insert("natives".into(), to_value(natives.natives())?);
+        }
+
+        if let Some(typedefs) = &self.rtti_typedefs {
+            rtti.insert("typedefs".into(), to_value(typedefs.typedefs())?);
+        }
+
+        if let Some(typesets) = &self.rtti_typesets {
+            rtti.insert("typesets".into(), to_value(typesets.typesets())?);
+        }
+
+        if let Some(enum_structs) = &self.rtti_enum_structs {
+            rtti.insert("enum_structs".into(), to_value(enum_structs.entries())?);
+        }
+
+        if let Some(classdefs) = &self.rtti_classdefs {
+            rtti.insert("classdefs".into(), to_value(classdefs.defs())?);
+        }
+
+        if !rtti.is_empty() {
+            doc.insert("rtti".into(), Value::Object(rtti));
+        }
+
+        let mut addrs: Vec<&i32> = self.functions.keys().collect();
+        addrs.sort();
+
+        let functions: Vec<Value> = addrs.into_iter().map(|addr| json!({
+            "name": self.find_function_name(*addr),
+            "address": addr,
+            "instructions": self.functions[addr].iter().map(|insn| json!({
+                "address": insn.address,
+                "text": insn.to_string(),
+            })).collect::<Vec<Value>>(),
+        })).collect();
+
+        doc.insert("functions".into(), Value::Array(functions));
+
+        serde_json::to_string_pretty(&Value::Object(doc)).map_err(|_| Error::Other("Failed to serialize"))
+    }
+
+    // Number of arguments the native at |index| declares, from its RTTI
+    // signature. A variadic native counts its repeated argument once.
+    pub fn native_arg_count(&self, index: usize) -> Result<Option<u32>> {
+        let natives = self.natives.as_ref().ok_or(Error::MissingSection(".natives"))?;
+
+        if index >= natives.size() {
+            return Err(Error::InvalidIndex)
+        }
+
+        let name: String = natives.get_entry(index).name;
+
+        let native = match &self.rtti_natives {
+            Some(rtti_natives) => rtti_natives.natives().into_iter().find(|n| n.name == name),
+            None => None,
+        };
+
+        match (native, &self.rtti_data) {
+            (Some(native), Some(rtti_data)) => Ok(Some(rtti_data.function_from_offset(native.signature)?.args.len() as u32)),
+            _ => Ok(None),
+        }
+    }
+
+    // Whether the function type at |signature_offset| in rtti.data takes
+    // variable arguments.
+    pub fn is_variadic(&self, signature_offset: i32) -> Result<bool> {
+        Ok(self.rtti_data.as_ref().ok_or(Error::MissingSection("rtti.data"))?.function_from_offset(signature_offset)?.variadic)
+    }
+
+    // Decode an RTTI type id, as carried by fields and enum struct fields.
+    pub fn type_name(&self, type_id: i32) -> Result<String> {
+        self.rtti_data.as_ref().ok_or(Error::MissingSection("rtti.data"))?.type_from_id(type_id)
+    }
+
+    // Resolve typedef |index| to the type it aliases, following typedefs of
+    // typedefs. A chain that leads back to a typedef already visited is an
+    // error.
+    pub fn expand_typedef(&self, index: usize) -> Result<String> {
+        let typedefs = self.rtti_typedefs.as_ref().ok_or(Error::MissingSection("rtti.typedefs"))?.typedefs();
+        let rtti_data = self.rtti_data.as_ref().ok_or(Error::MissingSection("rtti.data"))?;
+
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut index: usize = index;
+        let mut is_const: bool = false;
+
+        loop {
+            if !seen.insert(index) {
+                return Err(Error::Other("Cyclic typedef"))
+            }
+
+            let type_id = typedefs.get(index).ok_or(Error::InvalidIndex)?.type_id;
+
+            match rtti_data.typedef_index(type_id)? {
+                Some((next, next_const)) => {
+                    index = next;
+                    is_const |= next_const;
+                },
+                None => {
+                    let name = rtti_data.type_from_id(type_id)?;
+
+                    return Ok(match is_const && !name.starts_with("const ") {
+                        true => format!("const {}", name),
+                        false => name,
+                    })
+                },
+            }
+        }
+    }
+
+    // The signature of the RTTI method whose code holds a public function,
+    // e.g. "function void ()". None without RTTI or if it fails to decode.
+    pub fn public_signature(&self, pub_entry: &PublicEntry) -> Option<String> {
+        let method = self.rtti_methods.as_ref()?.method_at(pub_entry.address as i32)?;
+
+        self.rtti_data.as_ref()?.function_type_from_offset(method.signature).ok()
+    }
+
+    // Whether a class field is declared const. Field flags carry no
+    // qualifiers; const is part of the field's type.
+    pub fn is_const_field(&self, field: &RTTIField) -> Result<bool> {
+        Ok(self.rtti_data.as_ref().ok_or(Error::MissingSection("rtti.data"))?.type_info(field.type_id)?.is_const)
+    }
+
+    // Decode a method's signature, e.g. "function int (float, char[])".
+    pub fn method_signature(&self, method: &RTTIMethod) -> Result<String> {
+        self.rtti_data.as_ref().ok_or(Error::MissingSection("rtti.data"))?.function_type_from_offset(method.signature)
+    }
+
+    // Decode a native's signature from its RTTI entry.
+    pub fn native_signature(&self, native: &RTTINative) -> Result<String> {
+        self.rtti_data.as_ref().ok_or(Error::MissingSection("rtti.data"))?.function_type_from_offset(native.signature)
+    }
+
+    // Disassemble every method of a methodmap. The compiler names methodmap
+    // methods |Class.method| (properties |Class.Prop.get|) in rtti.methods,
+    // and each body is decoded from that method's pcode range. Returns the
+    // method names with the class prefix removed.
+    pub fn methodmap_bodies(&self, class: &str) -> Result<Vec<(String, Vec<V1Instruction>)>> {
+        let methods = self.rtti_methods.as_ref().ok_or(Error::MissingSection("rtti.methods"))?;
+        let code = self.codev1.as_ref().ok_or(Error::MissingSection(".code"))?;
+
+        let data: Vec<u8> = code.get_data_vec();
+        let prefix: String = format!("{}.", class);
+
+        let mut bodies: Vec<(String, Vec<V1Instruction>)> = Vec::new();
+
+        for method in methods.methods_ref() {
+            let name = match method.name.strip_prefix(prefix.as_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+
+            if method.pcode_start < 0 || method.pcode_start >= method.pcode_end || method.pcode_end as usize > data.len() {
+                return Err(Error::OffsetOverflow)
+            }
+
+            let insns = V1Disassembler::disassemble_bytes(&data[..method.pcode_end as usize], method.pcode_start)?;
+
+            bodies.push((name.to_string(), insns));
+        }
+
+        Ok(bodies)
+    }
+
+    // Recover the members of a pre-enumstruct enum. Legacy plugins carry no
+    // member table and never export enum constants, so the enum only
+    // survives as a tag with the ENUM flag. Its members are correlated by
+    // tag id: every legacy .dbg.symbols variable whose record carries the
+    // enum's tag, in symbol table order. Functions returning the tag are
+    // skipped, and a tag without the ENUM flag has no members.
+    pub fn legacy_enum_members(&self, tag: &Tag) -> Vec<String> {
+        let mut members: Vec<String> = Vec::new();
+
+        if tag.flags() & TagEntry::ENUM == 0 {
+            return members;
+        }
+
+        let symbols = match &self.debug_symbols {
+            Some(symbols) => symbols,
+            None => return members,
+        };
+
+        for sym in symbols.entries_ref() {
+            if sym.ident == DebugSymbolEntry::IDENT_FUNCTION || sym.tag_id as u16 as u32 != tag.id() {
+                continue;
+            }
+
+            if !members.contains(&sym.name) {
+                members.push(sym.name.clone());
+            }
+        }
+
+        members
+    }
+}
+
+// Tarjan's algorithm over a call graph. Every node ends up in exactly one
+// component; callees missing from the graph are ignored.
+fn strongly_connected_components(graph: &HashMap<i32, Vec<i32>>) -> Vec<Vec<i32>> {
+    struct State<'a> {
+        graph: &'a HashMap<i32, Vec<i32>>,
+        next_index: usize,
+        index: HashMap<i32, usize>,
+        lowlink: HashMap<i32, usize>,
+        stack: Vec<i32>,
+        on_stack: HashSet<i32>,
+        components: Vec<Vec<i32>>,
+    }
+
+    fn connect(state: &mut State, node: i32) {
+        state.index.insert(node, state.next_index);
+        state.lowlink.insert(node, state.next_index);
+        state.next_index += 1;
+        state.stack.push(node);
+        state.on_stack.insert(node);
+
+        for callee in &state.graph[&node] {
+            if !state.graph.contains_key(callee) {
+                continue;
+            }
+
+            if !state.index.contains_key(callee) {
+                connect(state, *callee);
+
+                let low = state.lowlink[&node].min(state.lowlink[callee]);
+                state.lowlink.insert(node, low);
+            } else if state.on_stack.contains(callee) {
+                let low = state.lowlink[&node].min(state.index[callee]);
+                state.lowlink.insert(node, low);
+            }
+        }
+
+        if state.lowlink[&node] == state.index[&node] {
+            let mut component: Vec<i32> = Vec::new();
+
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(&member);
+                component.push(member);
+
+                if member == node {
+                    break;
+                }
+            }
+
+            state.components.push(component);
+        }
+    }
+
+    let mut state = State {
+        graph,
+        next_index: 0,
+        index: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: HashSet::new(),
+        components: Vec::new(),
+    };
+
+    let mut nodes: Vec<&i32> = graph.keys().collect();
+    nodes.sort();
+
+    for node in nodes {
+        if !state.index.contains_key(node) {
+            connect(&mut state, *node);
+        }
+    }
+
+    state.components
+}
@@ -1,21 +1,19 @@
 use std::rc::Rc;
+use std::collections::HashMap;
 use std::io::{Read, Seek, SeekFrom, Cursor};
 use byteorder::{ReadBytesExt, LittleEndian};
 use flate2::read::ZlibDecoder;
 use std::fmt;
 use crate::errors::{Result, Error};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub enum CompressionType {
     CompressionNone,
+    #[default]
     CompressionGZ,
     CompressionUnknown,
 }
 
-impl Default for CompressionType {
-    fn default() -> Self { CompressionType::CompressionGZ }
-}
-
 impl From<u8> for CompressionType {
     fn from(byte: u8) -> Self {
         match byte {
@@ -147,10 +145,39 @@ impl SMXHeader {
     // arrays use direct internal addressing.
     pub const K_CODE_FEATURE_DIRECT_ARRAYS: u32 = (1 << 1);
 
+    // Heap allocations are scoped with HEAP_SAVE and HEAP_RESTORE.
+    pub const K_CODE_FEATURE_HEAP_SCOPES: u32 = (1 << 2);
+
     // Size of the header.
     const HEADER_SIZE: i32 = 24;
 
+    // Upper bound on how far zlib can inflate its input.
+    const MAX_COMPRESSION_RATIO: usize = 1032;
+
+    // Read a whole file from |reader| and parse its header.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<SMXHeader> {
+        let mut data: Vec<u8> = Vec::new();
+
+        reader.read_to_end(&mut data)?;
+
+        SMXHeader::new(data)
+    }
+
+    // Parse a header, rejecting versions this parser does not support.
     pub fn new<T>(data: T) -> Result<SMXHeader>
+    where
+        T: AsRef<[u8]>,
+    {
+        let header = SMXHeader::new_lenient(data)?;
+
+        header.check_version()?;
+
+        Ok(header)
+    }
+
+    // Parse a header whatever its version, so files from other compiler
+    // versions can still be inspected or round-tripped.
+    pub fn new_lenient<T>(data: T) -> Result<SMXHeader>
     where
         T: AsRef<[u8]>,
     {
@@ -192,7 +219,15 @@ impl SMXHeader {
             return Err(Error::InvalidOffset)
         }
 
-        let mut p_data: Vec<u8> = Vec::with_capacity(image_size as usize);
+        let input_size = data.get_ref().as_ref().len();
+
+        if matches!(compression_type, CompressionType::CompressionNone) && image_size as usize > input_size {
+            return Err(Error::SizeOverflow)
+        }
+
+        // |image_size| is only a hint for compressed images; don't trust it
+        // with more than the decompressor could plausibly produce.
+        let mut p_data: Vec<u8> = Vec::with_capacity((image_size as usize).min(input_size.saturating_mul(SMXHeader::MAX_COMPRESSION_RATIO)));
 
         p_data.extend(&data.get_ref().as_ref()[..SMXHeader::HEADER_SIZE as usize]);
 
@@ -254,7 +289,13 @@ impl SMXHeader {
                     size
                 },
                 name: {
-                    let mut cursor = Cursor::new(&cloned_data[string_table_offset as usize + name_offset as usize..]);
+                    let name_start = string_table_offset as usize + name_offset as usize;
+
+                    if name_start >= cloned_data.len() {
+                        return Err(Error::OffsetOverflow)
+                    }
+
+                    let mut cursor = Cursor::new(&cloned_data[name_start..]);
 
                     let name = cursor.read_cstring()?;
 
@@ -267,6 +308,14 @@ impl SMXHeader {
             }))
         }
 
+        // Section contents are sliced straight out of the image later on.
+        for section in &sections {
+            match section.data_offset.checked_add(section.size) {
+                Some(end) if end as usize <= cloned_data.len() => (),
+                _ => return Err(Error::SizeOverflow),
+            }
+        }
+
         Ok(SMXHeader{
             magic: SMXHeader::FILE_MAGIC,
             version,
@@ -282,11 +331,46 @@ impl SMXHeader {
         })
     }
 
-    // fn string_at(&self, index: usize) -> Result<String> {
-    //     let mut data = Cursor::new(&self.data[self.string_table_offset as usize + index..]);
+    // First section named |name|.
+    pub fn find_section(&self, name: &str) -> Option<&SectionEntry> {
+        self.sections.iter().find(|section| section.name == name).map(|section| &**section)
+    }
+
+    // Sections keyed by name. Should a name repeat, the first one wins, as
+    // with find_section.
+    pub fn sections_by_name(&self) -> HashMap<&str, &SectionEntry> {
+        let mut sections: HashMap<&str, &SectionEntry> = HashMap::with_capacity(self.sections.len());
 
-    //     return data.read_cstring();
-    // }
+        for section in &self.sections {
+            sections.entry(section.name.as_str()).or_insert(section);
+        }
+
+        sections
+    }
+
+    // Reject versions for another product or newer than this parser knows.
+    pub fn check_version(&self) -> Result<()> {
+        let same_product = self.version >> 8 == SMXHeader::SP1_VERSION_MAX >> 8;
+
+        if !same_product || self.version < SMXHeader::SP1_VERSION_MIN || self.version > SMXHeader::SP1_VERSION_MAX {
+            return Err(Error::UnsupportedVersion(self.version))
+        }
+
+        Ok(())
+    }
+
+    // Read a string from the container string table, which holds the
+    // section names.
+    pub fn string_at(&self, index: i32) -> Result<String> {
+        let start = match self.string_table_offset.checked_add(index) {
+            Some(start) if index >= 0 && (start as usize) < self.data.len() => start as usize,
+            _ => return Err(Error::InvalidIndex),
+        };
+
+        let mut data = Cursor::new(&self.data[start..]);
+
+        data.read_cstring()
+    }
 }
 
 impl fmt::Debug for SMXHeader {
@@ -304,7 +388,8 @@ impl fmt::Debug for SMXHeader {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SectionEntry {
     // Offset into the string table.
     pub name_offset: i32,
@@ -11,3 +11,7 @@ pub mod rtti;
 pub mod file;
 pub mod v1opcodes;
 pub mod v1disassembler;
+pub mod v1listing;
+pub mod v1cfg;
+#[cfg(feature = "test-utils")]
+pub mod builder;
\ No newline at end of file
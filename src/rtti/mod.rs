@@ -1,11 +1,13 @@
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::io::{Cursor, Seek, SeekFrom};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Cursor;
 use byteorder::{ReadBytesExt, LittleEndian};
 use crate::sections::{BaseSection, SMXNameTable};
 use crate::headers::{SMXHeader, SectionEntry};
 use crate::file::SMXFile;
-use crate::errors::Result;
+use crate::errors::{Result, Error};
 
 #[derive(Debug, Clone)]
 pub struct SMXRTTIListTable {
@@ -19,6 +21,9 @@ pub struct SMXRTTIListTable {
 }
 
 impl SMXRTTIListTable {
+    // Size of the header_size, row_size and row_count fields.
+    const HEADER_SIZE: u32 = 12;
+
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>) -> Self {
         Self {
             base: BaseSection::new(header, section),
@@ -28,14 +33,40 @@ impl SMXRTTIListTable {
         }
     }
 
-    pub fn init(&mut self, data: &mut Cursor<Vec<u8>>) -> Result<&Self> {
+    pub fn init<T: AsRef<[u8]>>(&mut self, data: &mut Cursor<T>) -> Result<&Self> {
         self.header_size = data.read_u32::<LittleEndian>()?;
         self.row_size = data.read_u32::<LittleEndian>()?;
         self.row_count =data.read_u32::<LittleEndian>()?;
 
+        if self.header_size != Self::HEADER_SIZE {
+            return Err(Error::InvalidSize)
+        }
+
+        // Tables are sized from the row count before any row is read, so a
+        // count the section cannot hold is rejected up front.
+        let rows = self.row_size as u64 * self.row_count as u64;
+
+        if (self.row_size == 0 && self.row_count > 0) || self.header_size as u64 + rows > self.base.section.size as u64 {
+            return Err(Error::SizeOverflow)
+        }
+
         Ok(self)
     }
 
+    // Rows must hold at least the |size| bytes of fields that are read from
+    // them. Wider rows are fine; the extra fields are skipped.
+    pub fn check_row_size(&self, size: u32) -> Result<()> {
+        match self.row_size < size {
+            true => Err(Error::InvalidSize),
+            false => Ok(()),
+        }
+    }
+
+    // Move |data| to the start of row |index|.
+    pub fn seek_row<T: AsRef<[u8]>>(&self, data: &mut Cursor<T>, index: u32) {
+        data.set_position(self.header_size as u64 + index as u64 * self.row_size as u64);
+    }
+
     pub fn header_size(&self) -> u32 {
         self.header_size
     }
@@ -77,7 +108,7 @@ impl CB {
     pub const TYPEID_INLINE: u8 = 0x0;
     pub const TYPEID_COMPLEX: u8 = 0x1;
 
-    pub fn decode_u32<T>(bytes: T, offset: &mut i32) -> i32
+    pub fn decode_u32<T>(bytes: T, offset: &mut i32) -> Result<i32>
     where
         T: AsRef<[u8]>,
     {
@@ -87,24 +118,72 @@ impl CB {
         let mut shift: i32 = 0;
 
         loop {
-            let b: u8 = bytes.get_ref().as_ref()[*offset as usize];
+            let b: u8 = match bytes.get_ref().as_ref().get(*offset as usize) {
+                Some(b) if *offset >= 0 => *b,
+                _ => return Err(Error::OffsetOverflow),
+            };
             *offset += 1;
-            value |= ((b & 0x7f) << shift) as u32;
+            value |= ((b & 0x7f) as u32) << shift;
             if (b & 0x80) == 0 {
                 break;
             }
             shift += 7;
+
+            // A u32 never needs more than five groups.
+            if shift >= 32 {
+                return Err(Error::SizeOverflow)
+            }
         }
 
-        value as i32
+        Ok(value as i32)
     }
 }
 
+// A decoded function signature. By-reference arguments carry a trailing "&".
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionType {
+    pub return_type: String,
+
+    pub args: Vec<String>,
+
+    // The last argument repeats.
+    pub variadic: bool,
+}
+
+impl fmt::Display for FunctionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "function {} ({}", self.return_type, self.args.join(", "))?;
+
+        if self.variadic {
+            write!(f, "...")?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+// A decoded type with its qualifier split out of the name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeInfo {
+    // The type without a leading "const ".
+    pub name: String,
+
+    pub is_const: bool,
+
+    // The outermost type code, e.g. CB::ENUM or CB::ARRAY.
+    pub kind: u8,
+}
+
 #[derive(Clone)]
 pub struct SMXRTTIData {
     smx_file: Rc<RefCell<SMXFile>>,
 
     bytes: Vec<u8>,
+
+    // Decoded names, keyed by type id and by signature offset. Only
+    // successful decodes are cached.
+    type_names: RefCell<HashMap<i32, String>>,
+    function_types: RefCell<HashMap<i32, String>>,
 }
 
 impl SMXRTTIData {
@@ -114,74 +193,125 @@ impl SMXRTTIData {
         Self {
             smx_file: Rc::clone(&file),
             bytes: base.get_data(),
+            type_names: RefCell::new(HashMap::new()),
+            function_types: RefCell::new(HashMap::new()),
         }
     }
 
-    pub fn type_from_id(&self, type_id: i32) -> String {
-        let kind: i32 = type_id & 0xf;
-        let mut payload: i32 = (type_id >> 4) & 0x0fff_ffff;
+    // The type encoding packed into an inline typeid, or None for a typeid
+    // that refers into rtti.data.
+    pub fn inline_bytes(type_id: i32) -> Option<[u8; 4]> {
+        if type_id & 0xf != CB::TYPEID_INLINE as i32 {
+            return None
+        }
 
-        if kind == CB::TYPEID_INLINE as i32 {
-            let temp: [u8; 4] = [
-                (payload & 0xff) as u8,
-                (payload >> 8) as u8,
-                (payload >> 16) as u8,
-                (payload >> 24) as u8,
-            ];
+        let payload: i32 = (type_id >> 4) & 0x0fff_ffff;
 
-            let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), temp.to_vec(), 0);
+        Some([
+            (payload & 0xff) as u8,
+            (payload >> 8) as u8,
+            (payload >> 16) as u8,
+            (payload >> 24) as u8,
+        ])
+    }
 
-            return builder.decode_new()
+    pub fn type_from_id(&self, type_id: i32) -> Result<String> {
+        if let Some(name) = self.type_names.borrow().get(&type_id) {
+            return Ok(name.clone())
         }
 
-        //TODO: Consider convert to Result<String>
-        if kind != CB::TYPEID_COMPLEX as i32 {
-            return format!("Unknown type_id kind: {}", kind);
+        let name: String = self.decode_id(type_id, |builder| builder.decode_new())?;
+
+        self.type_names.borrow_mut().insert(type_id, name.clone());
+
+        Ok(name)
+    }
+
+    // Like type_from_id, but reports const and the type's kind separately
+    // instead of folding them into the name.
+    pub fn type_info(&self, type_id: i32) -> Result<TypeInfo> {
+        self.decode_id(type_id, |builder| builder.decode_info())
+    }
+
+    // The typedef a type id names directly, as (index, is_const), or None for
+    // any other kind of type.
+    pub fn typedef_index(&self, type_id: i32) -> Result<Option<(usize, bool)>> {
+        self.decode_id(type_id, |builder| builder.decode_typedef_index())
+    }
+
+    pub fn function_type_from_offset(&self, offset: i32) -> Result<String> {
+        if let Some(name) = self.function_types.borrow().get(&offset) {
+            return Ok(name.clone())
         }
 
-        self.build_type_name(&mut payload)
+        let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), &self.bytes, offset);
+
+        let name: String = builder.decode_function()?;
+
+        self.function_types.borrow_mut().insert(offset, name.clone());
+
+        Ok(name)
     }
 
-    pub fn function_type_from_offset(&self, offset: i32) -> String {
-        let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), self.bytes.clone(), offset);
+    pub fn function_from_offset(&self, offset: i32) -> Result<FunctionType> {
+        let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), &self.bytes, offset);
 
-        builder.decode_function()
+        builder.decode_function_type()
     }
 
-    pub fn typeset_types_from_offset(&self, offset: i32) -> Vec<String> {
-        let count: i32 = CB::decode_u32(&self.bytes, &mut offset.clone());
+    pub fn typeset_types_from_offset(&self, offset: i32) -> Result<Vec<String>> {
+        let mut offset: i32 = offset;
 
-        let mut types: Vec<String> = Vec::with_capacity(count as usize);
+        let count: i32 = CB::decode_u32(&self.bytes, &mut offset)?;
 
-        let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), self.bytes.clone(), offset);
+        // Every member takes at least a byte, so a bogus count can't allocate
+        // more than the section holds.
+        let mut types: Vec<String> = Vec::with_capacity((count as u32 as usize).min(self.bytes.len()));
+
+        let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), &self.bytes, offset);
 
         for _ in 0..count {
-            types.push(builder.decode_new())
+            types.push(builder.decode_new()?)
         }
 
-        types
+        Ok(types)
+    }
+
+    // A typeset's members as one type, e.g. "int | float".
+    pub fn typeset_display_from_offset(&self, offset: i32) -> Result<String> {
+        Ok(self.typeset_types_from_offset(offset)?.join(" | "))
     }
 
-    fn build_type_name(&self, offset: &mut i32) -> String {
-        let mut builder: TypeBuilder = TypeBuilder::new(Rc::clone(&self.smx_file), self.bytes.clone(), *offset);
+    // Inline typeids carry their encoding in the id itself; complex ones
+    // point into rtti.data.
+    fn decode_id<R, F>(&self, type_id: i32, decode: F) -> Result<R>
+    where
+        F: FnOnce(&mut TypeBuilder) -> Result<R>,
+    {
+        let kind: i32 = type_id & 0xf;
+        let payload: i32 = (type_id >> 4) & 0x0fff_ffff;
 
-        let text: String = builder.decode_new();
+        if let Some(temp) = SMXRTTIData::inline_bytes(type_id) {
+            return decode(&mut TypeBuilder::new(Rc::clone(&self.smx_file), &temp, 0))
+        }
 
-        *offset = builder.offset;
+        if kind != CB::TYPEID_COMPLEX as i32 {
+            return Err(Error::UnknownTypeCode(kind as u8))
+        }
 
-        text
+        decode(&mut TypeBuilder::new(Rc::clone(&self.smx_file), &self.bytes, payload))
     }
 }
 
-struct TypeBuilder {
+struct TypeBuilder<'a> {
     file: Rc<RefCell<SMXFile>>,
-    bytes: Vec<u8>,
+    bytes: &'a [u8],
     offset: i32,
     is_const: bool,
 }
 
-impl TypeBuilder{
-    pub fn new(file: Rc<RefCell<SMXFile>>, bytes: Vec<u8>, offset: i32) -> Self {
+impl<'a> TypeBuilder<'a> {
+    pub fn new(file: Rc<RefCell<SMXFile>>, bytes: &'a [u8], offset: i32) -> Self {
         Self {
             file,
             bytes,
@@ -192,11 +322,11 @@ impl TypeBuilder{
 
     // Decode a type, but reset the |is_const| indicator for non-
     // dependent type.
-    pub fn decode_new(&mut self) -> String {
+    pub fn decode_new(&mut self) -> Result<String> {
         let was_const: bool = self.is_const;
         self.is_const = false;
 
-        let mut result: String = self.decode();
+        let mut result: String = self.decode()?;
 
         if self.is_const {
             result = format!("const {}", result);
@@ -204,15 +334,40 @@ impl TypeBuilder{
 
         self.is_const = was_const;
 
-        result
+        Ok(result)
     }
 
-    pub fn decode(&mut self) -> String {
-        self.is_const |= self.r#match(CB::CONST);
-        let b: u8 = self.bytes[self.offset as usize];
-        self.offset += 1;
+    pub fn decode_info(&mut self) -> Result<TypeInfo> {
+        self.is_const = false;
+        self.is_const |= self.r#match(CB::CONST)?;
+
+        let kind: u8 = self.peek()?;
+        let name: String = self.decode()?;
 
-        match b {
+        Ok(TypeInfo {
+            name,
+            is_const: self.is_const,
+            kind,
+        })
+    }
+
+    pub fn decode_typedef_index(&mut self) -> Result<Option<(usize, bool)>> {
+        let is_const: bool = self.r#match(CB::CONST)?;
+
+        if !self.r#match(CB::TYPEDEF)? {
+            return Ok(None)
+        }
+
+        let index = CB::decode_u32(self.bytes, &mut self.offset)?;
+
+        Ok(Some((index as u32 as usize, is_const)))
+    }
+
+    pub fn decode(&mut self) -> Result<String> {
+        self.is_const |= self.r#match(CB::CONST)?;
+        let b: u8 = self.next()?;
+
+        Ok(match b {
             CB::BOOL => "bool".into(),
             CB::INT32 => "int".into(),
             CB::FLOAT32 => "float".into(),
@@ -220,98 +375,144 @@ impl TypeBuilder{
             CB::ANY => "any".into(),
             CB::TOPFUNCTION => "Function".into(),
             CB::FIXEDARRAY => {
-                let index = CB::decode_u32(&self.bytes, &mut self.offset);
-                let inner: String = self.decode();
+                let index = CB::decode_u32(self.bytes, &mut self.offset)?;
+                let inner: String = self.decode()?;
 
                 format!("{}[{}]", inner, index)
             },
             CB::ARRAY => {
-                let inner: String = self.decode();
+                let inner: String = self.decode()?;
                 
                 format!("{}[]", inner)
             },
             CB::ENUM => {
-                let index = CB::decode_u32(&self.bytes, &mut self.offset);
+                let index = CB::decode_u32(self.bytes, &mut self.offset)?;
 
-                self.file.borrow_mut().rtti_enums.as_ref().unwrap().enums()[index as usize].clone()
+                let file = self.file.borrow();
+                let enums = file.rtti_enums.as_ref().ok_or(Error::MissingSection("rtti.enums"))?.enums();
+
+                enums.get(index as usize).ok_or(Error::InvalidIndex)?.clone()
             },
             CB::TYPEDEF => {
-                let index = CB::decode_u32(&self.bytes, &mut self.offset);
+                let index = CB::decode_u32(self.bytes, &mut self.offset)?;
+
+                let file = self.file.borrow();
+                let typedefs = file.rtti_typedefs.as_ref().ok_or(Error::MissingSection("rtti.typedefs"))?.typedefs();
 
-                self.file.borrow_mut().rtti_typedefs.as_ref().unwrap().typedefs()[index as usize].name.clone()
+                typedefs.get(index as usize).ok_or(Error::InvalidIndex)?.name.clone()
             }
             CB::TYPESET => {
-                let index = CB::decode_u32(&self.bytes, &mut self.offset);
+                let index = CB::decode_u32(self.bytes, &mut self.offset)?;
 
-                self.file.borrow_mut().rtti_typesets.as_ref().unwrap().typesets()[index as usize].name.clone()
+                let file = self.file.borrow();
+                let typesets = file.rtti_typesets.as_ref().ok_or(Error::MissingSection("rtti.typesets"))?.typesets();
+
+                typesets.get(index as usize).ok_or(Error::InvalidIndex)?.name.clone()
             },
             CB::STRUCT => {
-                let index = CB::decode_u32(&self.bytes, &mut self.offset);
+                let index = CB::decode_u32(self.bytes, &mut self.offset)?;
+
+                let file = self.file.borrow();
+                let defs = file.rtti_classdefs.as_ref().ok_or(Error::MissingSection("rtti.classdefs"))?.defs();
 
-                self.file.borrow_mut().rtti_classdefs.as_ref().unwrap().defs()[index as usize].name.clone()
+                defs.get(index as usize).ok_or(Error::InvalidIndex)?.name.clone()
             },
-            CB::FUNCTION => self.decode_function(),
+            CB::FUNCTION => self.decode_function()?,
             CB::ENUMSTRUCT => {
-                let index = CB::decode_u32(&self.bytes, &mut self.offset);
+                let index = CB::decode_u32(self.bytes, &mut self.offset)?;
+
+                let file = self.file.borrow();
+                let entries = file.rtti_enum_structs.as_ref().ok_or(Error::MissingSection("rtti.enumstructs"))?.entries();
 
-                self.file.borrow_mut().rtti_enum_structs.as_ref().unwrap().entries()[index as usize].name.clone()
+                entries.get(index as usize).ok_or(Error::InvalidIndex)?.name.clone()
             },
-            _ => format!("unknown type code: {}", b),
-        }
+            _ => return Err(Error::UnknownTypeCode(b)),
+        })
     }
 
-    pub fn decode_function(&mut self) -> String {
-        let argc: u32 = self.bytes[self.offset as usize] as u32;
-        self.offset += 1;
+    pub fn decode_function(&mut self) -> Result<String> {
+        Ok(self.decode_function_type()?.to_string())
+    }
 
-        let mut variadic: bool = false;
+    pub fn decode_function_type(&mut self) -> Result<FunctionType> {
+        let argc: u32 = self.next()? as u32;
 
-        if self.bytes[self.offset as usize] == CB::VARIADIC {
-            variadic = true;
-            self.offset += 1;
-        }
+        let variadic: bool = self.r#match(CB::VARIADIC)?;
 
-        let return_type: String;
+        // A const on void has no meaning and is dropped.
+        let (is_const, _) = self.qualifiers()?;
 
-        if self.bytes[self.offset as usize] == CB::VOID {
-            return_type = "void".into();
-            self.offset += 1;
-        } else {
-            return_type = self.decode_new();
-        }
+        let return_type: String = match self.r#match(CB::VOID)? {
+            true => "void".into(),
+            false => Self::with_const(self.decode_new()?, is_const),
+        };
 
-        let mut argv: Vec<String> = Vec::with_capacity(argc as usize);
+        let mut args: Vec<String> = Vec::with_capacity(argc as usize);
 
         for _ in 0..argc {
-            let is_byref: bool = self.r#match(CB::BYREF);
-            let mut text: String = self.decode_new();
+            let (is_const, is_byref) = self.qualifiers()?;
+            let mut text: String = Self::with_const(self.decode_new()?, is_const);
 
             if is_byref {
                 text += "&";
             }
 
-            argv.push(text);
+            args.push(text);
         }
 
-        let mut signature: String = format!("function {} ({}", return_type, argv.join(", "));
+        Ok(FunctionType {
+            return_type,
+            args,
+            variadic,
+        })
+    }
+
+    // Consume the const and by-ref markers leading an argument or return
+    // type, in whichever order they were emitted. Returns (const, byref).
+    fn qualifiers(&mut self) -> Result<(bool, bool)> {
+        let mut is_const: bool = false;
+        let mut is_byref: bool = false;
+
+        loop {
+            if self.r#match(CB::CONST)? {
+                is_const = true;
+            } else if self.r#match(CB::BYREF)? {
+                is_byref = true;
+            } else {
+                return Ok((is_const, is_byref))
+            }
+        }
+    }
+
+    fn with_const(text: String, is_const: bool) -> String {
+        match is_const && !text.starts_with("const ") {
+            true => format!("const {}", text),
+            false => text,
+        }
+    }
 
-        if variadic {
-            signature += "...";
+    fn peek(&self) -> Result<u8> {
+        match self.bytes.get(self.offset as usize) {
+            Some(b) if self.offset >= 0 => Ok(*b),
+            _ => Err(Error::InvalidIndex),
         }
+    }
 
-        signature += ")";
+    fn next(&mut self) -> Result<u8> {
+        let b: u8 = self.peek()?;
+        self.offset += 1;
 
-        signature
+        Ok(b)
     }
 
-    fn r#match(&mut self, b: u8) -> bool {
-        if self.bytes[self.offset as usize] != b {
-            return false
+    fn r#match(&mut self, b: u8) -> Result<bool> {
+        if self.peek()? != b {
+            return Ok(false)
         }
 
         self.offset += 1;
 
-        true
+        Ok(true)
     }
 }
 
@@ -325,19 +526,20 @@ impl SMXRTTIEnumTable {
         let base = BaseSection::new(header.clone(), section.clone());    
         let mut rtti = SMXRTTIListTable::new(header, section);
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(4)?;
+
         let mut enums: Vec<String> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let index = data.read_i32::<LittleEndian>()?;
 
             enums.push(names.borrow_mut().string_at(index)?);
-
-            // reserved0-2.
-            data.seek(SeekFrom::Current(3 * 4))?;
         }
 
         Ok(Self {
@@ -351,6 +553,7 @@ impl SMXRTTIEnumTable {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTIMethod {
     pub name: String,
 
@@ -361,9 +564,19 @@ pub struct RTTIMethod {
     pub signature: i32,
 }
 
+impl RTTIMethod {
+    pub fn is_variadic(&self, file: &SMXFile) -> Result<bool> {
+        file.is_variadic(self.signature)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SMXRTTIMethodTable {
     methods: Vec<RTTIMethod>,
+
+    // Indexes into |methods|, ordered by pcode_start. Compilers don't emit
+    // methods in code order.
+    by_address: Vec<usize>,
 }
 
 impl SMXRTTIMethodTable {
@@ -371,13 +584,17 @@ impl SMXRTTIMethodTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(16)?;
+
         let mut methods: Vec<RTTIMethod> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let index = data.read_i32::<LittleEndian>()?;
 
             methods.push(RTTIMethod {
@@ -388,8 +605,13 @@ impl SMXRTTIMethodTable {
             });
         }
 
+        let mut by_address: Vec<usize> = (0..methods.len()).collect();
+
+        by_address.sort_by_key(|&i| methods[i].pcode_start);
+
         Ok(Self {
             methods,
+            by_address,
         })
     }
 
@@ -400,15 +622,45 @@ impl SMXRTTIMethodTable {
     pub fn methods_ref(&self) -> &Vec<RTTIMethod> {
         self.methods.as_ref()
     }
+
+    // The method whose code range holds |pcode|, found by binary search on
+    // the methods' start addresses.
+    pub fn method_at(&self, pcode: i32) -> Option<&RTTIMethod> {
+        let index = self.by_address.partition_point(|&i| self.methods[i].pcode_start <= pcode);
+        let method = &self.methods[self.by_address[index.checked_sub(1)?]];
+
+        match pcode < method.pcode_end {
+            true => Some(method),
+            false => None,
+        }
+    }
+
+    // Decode the signature of every method, as (name, signature) pairs.
+    pub fn resolved_signature(&self, rtti_data: &SMXRTTIData) -> Result<Vec<(String, String)>> {
+        let mut signatures: Vec<(String, String)> = Vec::with_capacity(self.methods.len());
+
+        for method in &self.methods {
+            signatures.push((method.name.clone(), rtti_data.function_type_from_offset(method.signature)?));
+        }
+
+        Ok(signatures)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTINative {
     pub name: String,
 
     pub signature: i32,
 }
 
+impl RTTINative {
+    pub fn is_variadic(&self, file: &SMXFile) -> Result<bool> {
+        file.is_variadic(self.signature)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SMXRTTINativeTable {
     natives: Vec<RTTINative>,
@@ -419,13 +671,17 @@ impl SMXRTTINativeTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(8)?;
+
         let mut natives: Vec<RTTINative> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let index = data.read_i32::<LittleEndian>()?;
 
             natives.push(RTTINative {
@@ -445,6 +701,7 @@ impl SMXRTTINativeTable {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTITypedef {
     pub name: String,
 
@@ -461,13 +718,17 @@ impl SMXRTTITypedefTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(8)?;
+
         let mut typedefs: Vec<RTTITypedef> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let index = data.read_i32::<LittleEndian>()?;
 
             typedefs.push(RTTITypedef {
@@ -487,6 +748,7 @@ impl SMXRTTITypedefTable {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTITypeset {
     pub name: String,
 
@@ -503,13 +765,17 @@ impl SMXRTTITypesetTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(8)?;
+
         let mut typesets: Vec<RTTITypeset> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let index = data.read_i32::<LittleEndian>()?;
 
             typesets.push(RTTITypeset {
@@ -529,6 +795,7 @@ impl SMXRTTITypesetTable {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTIEnumStruct {
     pub name_offset: i32,
 
@@ -549,13 +816,17 @@ impl SMXRTTIEnumStructTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(12)?;
+
         let mut entries: Vec<RTTIEnumStruct> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let name_offset = data.read_i32::<LittleEndian>()?;
             let first_field = data.read_i32::<LittleEndian>()?;
             let size = data.read_i32::<LittleEndian>()?;
@@ -577,9 +848,27 @@ impl SMXRTTIEnumStructTable {
     pub fn entries(&self) -> Vec<RTTIEnumStruct> {
         self.entries.clone()
     }
+
+    // Fields of the enum struct at |index|. Each entry's fields run from its
+    // first_field up to the next entry's, and the last entry's to the end of
+    // the table. An unknown index or a malformed range yields no fields.
+    pub fn fields_for<'a>(&self, index: usize, fields: &'a SMXRTTIEnumStructFieldTable) -> &'a [RTTIEnumStructField] {
+        let start = match self.entries.get(index) {
+            Some(entry) => entry.first_field.max(0) as usize,
+            None => return &[],
+        };
+
+        let end = match self.entries.get(index + 1) {
+            Some(next) => next.first_field.max(0) as usize,
+            None => fields.entries.len(),
+        };
+
+        fields.entries.get(start..end.min(fields.entries.len())).unwrap_or(&[])
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTIEnumStructField {
     pub name_offset: i32,
 
@@ -600,13 +889,17 @@ impl SMXRTTIEnumStructFieldTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(12)?;
+
         let mut entries: Vec<RTTIEnumStructField> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let name_offset = data.read_i32::<LittleEndian>()?;
             let type_id = data.read_i32::<LittleEndian>()?;
             let offset = data.read_i32::<LittleEndian>()?;
@@ -630,7 +923,15 @@ impl SMXRTTIEnumStructFieldTable {
     }
 }
 
+// rtti.classdefs flags. The low nibble holds the kind of class.
+bitflags! {
+    pub struct ClassDefFlags: u32 {
+        const TYPE_MASK = 0xf;
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTIClassDef {
     pub flags: i32,
 
@@ -641,6 +942,19 @@ pub struct RTTIClassDef {
     pub name: String,
 }
 
+impl RTTIClassDef {
+    // The only kind of class compilers emit.
+    pub const TYPE_STRUCT: u32 = 0;
+
+    pub fn flag_set(&self) -> ClassDefFlags {
+        ClassDefFlags::from_bits_truncate(self.flags as u32)
+    }
+
+    pub fn class_type(&self) -> u32 {
+        (self.flag_set() & ClassDefFlags::TYPE_MASK).bits()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SMXRTTIClassDefTable {
     defs: Vec<RTTIClassDef>,
@@ -651,13 +965,17 @@ impl SMXRTTIClassDefTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(12)?;
+
         let mut defs: Vec<RTTIClassDef> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let flags = data.read_i32::<LittleEndian>()?;
             let name_offset = data.read_i32::<LittleEndian>()?;
             let first_field = data.read_i32::<LittleEndian>()?;
@@ -669,9 +987,6 @@ impl SMXRTTIClassDefTable {
                 first_field,
                 name,
             });
-
-            // reserved0-3
-            data.seek(SeekFrom::Current(4 * 4))?;
         }
 
         Ok(Self {
@@ -682,9 +997,33 @@ impl SMXRTTIClassDefTable {
     pub fn defs(&self) -> Vec<RTTIClassDef> {
         self.defs.clone()
     }
+
+    // Fields of the class at |index|, laid out like enum struct fields: from
+    // its first_field up to the next class's, or to the end of the table.
+    pub fn fields_for<'a>(&self, index: usize, fields: &'a SMXRTTIFieldTable) -> &'a [RTTIField] {
+        let start = match self.defs.get(index) {
+            Some(def) => def.first_field.max(0) as usize,
+            None => return &[],
+        };
+
+        let end = match self.defs.get(index + 1) {
+            Some(next) => next.first_field.max(0) as usize,
+            None => fields.fields.len(),
+        };
+
+        fields.fields.get(start..end.min(fields.fields.len())).unwrap_or(&[])
+    }
+}
+
+// rtti.fields flags. No bits are defined; const is not a flag but part of
+// the field's type encoding (see SMXFile::is_const_field).
+bitflags! {
+    pub struct FieldFlags: u16 {
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct RTTIField {
     pub flags: i16,
 
@@ -695,6 +1034,12 @@ pub struct RTTIField {
     pub name: String,
 }
 
+impl RTTIField {
+    pub fn flag_set(&self) -> FieldFlags {
+        FieldFlags::from_bits_truncate(self.flags as u16)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SMXRTTIFieldTable {
     fields: Vec<RTTIField>,
@@ -705,13 +1050,17 @@ impl SMXRTTIFieldTable {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));    
         let mut rtti = SMXRTTIListTable::new(Rc::clone(&header), Rc::clone(&section));
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(10)?;
+
         let mut fields: Vec<RTTIField> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             let flags = data.read_i16::<LittleEndian>()?;
             let name_offset = data.read_i32::<LittleEndian>()?;
             let type_id = data.read_i32::<LittleEndian>()?;
@@ -733,4 +1082,15 @@ impl SMXRTTIFieldTable {
     pub fn fields(&self) -> Vec<RTTIField> {
         self.fields.clone()
     }
+
+    // Pair each field name with its decoded type.
+    pub fn fields_with_types(&self, file: &SMXFile) -> Result<Vec<(String, String)>> {
+        let mut fields: Vec<(String, String)> = Vec::with_capacity(self.fields.len());
+
+        for field in &self.fields {
+            fields.push((field.name.clone(), file.type_name(field.type_id)?));
+        }
+
+        Ok(fields)
+    }
 }
\ No newline at end of file
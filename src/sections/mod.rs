@@ -2,6 +2,7 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::io::Cursor;
+use byteorder::{ReadBytesExt, LittleEndian};
 use crate::headers::{SMXHeader, SectionEntry};
 use crate::v1types::*;
 use crate::rtti::{SMXRTTIListTable, RTTIMethod};
@@ -23,7 +24,12 @@ impl BaseSection {
     }
 
     pub fn get_data(&self) -> Vec<u8> {
-        self.header.data[self.section.data_offset as usize..(self.section.data_offset + self.section.size) as usize].to_vec()
+        self.get_data_ref().to_vec()
+    }
+
+    // The section contents, borrowed from the image.
+    pub fn get_data_ref(&self) -> &[u8] {
+        &self.header.data[self.section.data_offset as usize..(self.section.data_offset + self.section.size) as usize]
     }
 }
 
@@ -74,31 +80,58 @@ impl SMXNameTable {
         self.names.clone()
     }
 
+    // Every string in the table along with its offset, in table order.
+    pub fn iter(&mut self) -> impl Iterator<Item = (i32, String)> {
+        let strings: Vec<(i32, String)> = self.get_extends().into_iter()
+            .filter_map(|offset| self.string_at(offset).ok().map(|name| (offset, name)))
+            .collect();
+
+        strings.into_iter()
+    }
+
     // Returns a string at a given index.
     pub fn string_at(&mut self, index: i32) -> Result<String> {
         if self.names.contains_key(&index) {
             return Ok(self.names.get(&index).unwrap().clone())
         }
 
-        if index >= self.base.section.size {
-            return Err(Error::InvalidIndex)
-        }
+        let (bytes, _) = self.raw_string_at(index)?;
 
-        let mut str_vec = Vec::with_capacity(256);
+        let s = String::from_utf8_lossy(bytes).into_owned();
 
-        for i in index..self.base.section.size {
-            if self.base.header.data[(self.base.section.data_offset + i) as usize] == 0 {
-                break;
-            }
+        self.names.insert(index, s.clone());
+
+        Ok(s)
+    }
 
-            str_vec.push(self.base.header.data[(self.base.section.data_offset + i) as usize]);
+    // Like string_at, but fails on strings that run off the end of the
+    // section or are not valid UTF-8 instead of papering over them.
+    pub fn string_at_checked(&self, index: i32) -> Result<String> {
+        let (bytes, terminated) = self.raw_string_at(index)?;
+
+        if !terminated {
+            return Err(Error::Other("Unterminated string"))
         }
 
-        let s = String::from_utf8_lossy(&str_vec[..]).into_owned();
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(s.to_string()),
+            Err(_) => Err(Error::InvalidEncoding),
+        }
+    }
 
-        self.names.insert(index, s.clone());
+    // Bytes of the string at |index| up to its NUL, and whether a NUL was
+    // found before the end of the section.
+    fn raw_string_at(&self, index: i32) -> Result<(&[u8], bool)> {
+        if index < 0 || index >= self.base.section.size {
+            return Err(Error::InvalidIndex)
+        }
 
-        Ok(s)
+        let rest = &self.base.get_data_ref()[index as usize..];
+
+        match rest.iter().position(|b| *b == 0) {
+            Some(len) => Ok((&rest[..len], true)),
+            None => Ok((rest, false)),
+        }
     }
 }
 
@@ -111,7 +144,7 @@ pub struct SMXNativeTable {
 impl SMXNativeTable {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let natives = NativeEntry::new(&base.get_data(), section, names)?;
+        let natives = NativeEntry::new(base.get_data_ref(), section, names)?;
 
         Ok(Self {
             natives,
@@ -142,7 +175,7 @@ pub struct SMXPublicTable {
 impl SMXPublicTable {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let publics = PublicEntry::new(base.get_data(), section, names)?;
+        let publics = PublicEntry::new(base.get_data_ref(), section, names)?;
 
         Ok(Self {
             publics,
@@ -215,7 +248,7 @@ pub struct SMXPubvarTable {
 impl SMXPubvarTable {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let public_variables = PubvarEntry::new(base.get_data(), section, names)?;
+        let public_variables = PubvarEntry::new(base.get_data_ref(), section, names)?;
 
         Ok(Self {
             public_variables,
@@ -286,48 +319,35 @@ impl Tag {
 pub struct SMXTagTable {
     tags: Vec<Tag>,
 
-    cache: HashMap<u16, Tag>,
+    // Tag ids to the first tag carrying them, built up front so lookups
+    // work through a shared reference.
+    by_id: HashMap<u16, Tag>,
 }
 
 impl SMXTagTable {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let tags = TagEntry::new(base.get_data(), section, names)?;
+        let tags = TagEntry::new(base.get_data_ref(), section, names)?;
 
         let mut tt = Self {
-            tags: Vec::new(),
-            cache: HashMap::new(),
+            tags: Vec::with_capacity(tags.len()),
+            by_id: HashMap::with_capacity(tags.len()),
         };
 
         for tag in tags {
-            tt.tags.push(Tag::new(tag.to_owned()))
+            let tag = Tag::new(tag);
+
+            tt.by_id.entry(tag.id() as u16).or_insert_with(|| tag.clone());
+            tt.tags.push(tag);
         }
 
         Ok(tt)
     }
 
-    pub fn find_tag(&mut self, tag: u16) -> Option<Tag> {
-        if self.cache.contains_key(&tag) {
-            return Some(self.cache.get(&tag).unwrap().clone());
-        }
-
-        let mut found: Option<Tag> = None;
-
-        for i in 0..self.tags.len() {
-            if self.tags[i].id() as u16 == tag {
-                found = Some(self.tags[i].clone());
-                break;
-            }
-        }
-
-        if let Some(v) = &found {
-            self.cache.insert(tag, v.clone());
-        }
-
-        found
+    pub fn find_tag(&self, tag: u16) -> Option<Tag> {
+        self.by_id.get(&tag).cloned()
     }
 
-
     // Return a copy of the tag vector
     pub fn entries(&self) -> Vec<Tag> {
         self.tags.clone()
@@ -358,7 +378,12 @@ pub struct SMXDataSection{
 impl SMXDataSection {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let data_header = DataHeader::new(base.get_data())?;
+        let data_header = DataHeader::new(base.get_data_ref())?;
+
+        // The blob is sliced out of the section on every read.
+        if data_header.data_offset as u64 + data_header.data_size as u64 > section.size as u64 {
+            return Err(Error::SizeOverflow)
+        }
 
         Ok(Self {
             base,
@@ -366,15 +391,60 @@ impl SMXDataSection {
         })
     }
 
-    pub fn get_data_vec(&self) -> Vec<u8> {
-        let start = self.base.section.data_offset as u32 + self.data_header.data_offset;
+    // The initialized data blob, borrowed from the image.
+    fn blob(&self) -> &[u8] {
+        let start = self.data_header.data_offset as usize;
 
-        Vec::from(&self.base.header.data[start as usize..(start + self.data_header.data_size) as usize])
+        &self.base.get_data_ref()[start..start + self.data_header.data_size as usize]
+    }
+
+    pub fn get_data_vec(&self) -> Vec<u8> {
+        self.blob().to_vec()
     }
 
     pub fn header(&self) -> DataHeader {
         self.data_header.clone()
     }
+
+    pub fn memory_layout(&self) -> MemoryLayout {
+        MemoryLayout {
+            data_start: 0,
+            data_end: self.data_header.data_size,
+            heap_start: self.data_header.data_size,
+            stack_top: self.data_header.memory_size,
+        }
+    }
+
+    // Read a cell at an offset into the data blob.
+    pub fn read_cell(&self, offset: u32) -> Result<i32> {
+        let start = offset as usize;
+
+        let bytes = match start.checked_add(4) {
+            Some(end) => self.blob().get(start..end).ok_or(Error::OffsetOverflow)?,
+            None => return Err(Error::OffsetOverflow),
+        };
+
+        let mut cursor = Cursor::new(bytes);
+
+        Ok(cursor.read_i32::<LittleEndian>()?)
+    }
+
+    // Read a cell at an offset into the data blob as a float.
+    pub fn read_float(&self, offset: u32) -> Result<f32> {
+        Ok(f32::from_bits(self.read_cell(offset)? as u32))
+    }
+
+    // Returns the NUL-terminated string at an offset into the data blob.
+    pub fn string_at(&self, offset: u32) -> Result<String> {
+        let bytes: &[u8] = match self.blob().get(offset as usize..) {
+            Some(bytes) if !bytes.is_empty() => bytes,
+            _ => return Err(Error::InvalidIndex),
+        };
+
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    }
 }
 
 // The .code section.
@@ -388,7 +458,12 @@ pub struct SMXCodeV1Section{
 impl SMXCodeV1Section {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let code_header = CodeV1Header::new(base.get_data())?;
+        let code_header = CodeV1Header::new(base.get_data_ref())?;
+
+        // The code stream is sliced out of the section on every read.
+        if code_header.code_offset < 0 || code_header.code_size < 0 || code_header.code_offset as i64 + code_header.code_size as i64 > section.size as i64 {
+            return Err(Error::SizeOverflow)
+        }
 
         Ok(Self {
             base,
@@ -396,10 +471,15 @@ impl SMXCodeV1Section {
         })
     }
 
-    pub fn get_data_vec(&self) -> Vec<u8> {
-        let start = self.base.section.data_offset + self.code_header.code_offset;
+    // The code stream, borrowed from the image.
+    fn blob(&self) -> &[u8] {
+        let start = self.code_header.code_offset as usize;
 
-        Vec::from(&self.base.header.data[start as usize..(start + self.code_header.code_size) as usize])
+        &self.base.get_data_ref()[start..start + self.code_header.code_size as usize]
+    }
+
+    pub fn get_data_vec(&self) -> Vec<u8> {
+        self.blob().to_vec()
     }
 
     pub fn header(&self) -> CodeV1Header {
@@ -410,6 +490,18 @@ impl SMXCodeV1Section {
     pub fn code_start(&self) -> i32 {
         self.base.section.data_offset + self.code_header.code_offset
     }
+
+    // Read a cell at an offset relative to the start of the code stream.
+    pub fn read_cell(&self, offset: i32) -> Result<i32> {
+        let bytes = match offset.checked_add(4) {
+            Some(end) if offset >= 0 => self.blob().get(offset as usize..end as usize).ok_or(Error::OffsetOverflow)?,
+            _ => return Err(Error::OffsetOverflow),
+        };
+
+        let mut cursor = Cursor::new(bytes);
+
+        Ok(cursor.read_i32::<LittleEndian>()?)
+    }
 }
 
 // The .dbg.info section.
@@ -421,7 +513,7 @@ pub struct SMXDebugInfoSection {
 impl SMXDebugInfoSection {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let info = DebugInfoHeader::new(base.get_data())?;
+        let info = DebugInfoHeader::new(base.get_data_ref())?;
 
         Ok(Self {
             info,
@@ -454,7 +546,7 @@ pub struct SMXDebugFilesTable {
 impl SMXDebugFilesTable {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let entries = DebugFileEntry::new(base.get_data(), section, names)?;
+        let entries = DebugFileEntry::new(base.get_data_ref(), section, names)?;
 
         Ok(Self {
             entries,
@@ -510,14 +602,16 @@ pub struct SMXDebugLinesTable {
 impl SMXDebugLinesTable {
     pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>) -> Result<Self> {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
-        let entries = DebugLineEntry::new(base.get_data(), section)?;
+        let entries = DebugLineEntry::new(base.get_data_ref(), section)?;
 
         Ok(Self {
             entries,
         })
     }
 
-    pub fn find_file(&self, addr: u32) -> Option<u32> {
+    // Source line of the code at |addr|. Lines are stored 0-based and
+    // returned 1-based, as editors and compiler diagnostics number them.
+    pub fn find_line(&self, addr: u32) -> Option<u32> {
         let mut high = self.len() as i32;
         let mut low = -1;
 
@@ -538,6 +632,11 @@ impl SMXDebugLinesTable {
         Some(self.entries[low as usize].line + 1)
     }
 
+    #[deprecated(note = "returns a line, use find_line")]
+    pub fn find_file(&self, addr: u32) -> Option<u32> {
+        self.find_line(addr)
+    }
+
     // Return a copy of the tag vector
     pub fn entries(&self) -> Vec<DebugLineEntry> {
         self.entries.clone()
@@ -557,6 +656,115 @@ impl SMXDebugLinesTable {
     }
 }
 
+// The legacy ".dbg.natives" table: a count followed by variable-length
+// native records.
+#[derive(Debug, Clone)]
+pub struct SMXDebugNativesTable {
+    entries: Vec<DebugNativeEntry>,
+}
+
+impl SMXDebugNativesTable {
+    pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>) -> Result<Self> {
+        let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
+
+        let mut data = Cursor::new(base.get_data_ref());
+
+        let count = data.read_u32::<LittleEndian>()?;
+
+        // Don't trust the count for the allocation; each record takes at
+        // least DebugNativeEntry::SIZE bytes.
+        let mut entries: Vec<DebugNativeEntry> = Vec::with_capacity((count as usize).min(section.size as usize / DebugNativeEntry::SIZE as usize));
+
+        for _ in 0..count {
+            entries.push(DebugNativeEntry::new(&mut data, &names)?)
+        }
+
+        Ok(Self {
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> Vec<DebugNativeEntry> {
+        self.entries.clone()
+    }
+
+    pub fn entries_ref(&self) -> &Vec<DebugNativeEntry> {
+        self.entries.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// The legacy ".dbg.symbols" table. Records are variable length and carry no
+// count of their own; the count comes from .dbg.info when present, otherwise
+// records are read until the section ends.
+#[derive(Debug, Clone)]
+pub struct SMXDebugSymbolsTable {
+    entries: Vec<DebugSymbolEntry>,
+}
+
+impl SMXDebugSymbolsTable {
+    pub fn new(header: Rc<SMXHeader>, section: Rc<SectionEntry>, names: Rc<RefCell<SMXNameTable>>, count: Option<i32>) -> Result<Self> {
+        let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
+
+        let mut data = Cursor::new(base.get_data_ref());
+
+        let mut entries: Vec<DebugSymbolEntry> = Vec::new();
+
+        loop {
+            match count {
+                Some(count) if entries.len() as i32 >= count => break,
+                None if data.position() >= data.get_ref().len() as u64 => break,
+                _ => (),
+            }
+
+            entries.push(DebugSymbolEntry::new(&mut data, header.debug_packed, &names)?);
+        }
+
+        Ok(Self {
+            entries,
+        })
+    }
+
+    // A global or static variable whose storage holds |addr|.
+    pub fn find_global(&self, addr: i32) -> Option<DebugSymbolEntry> {
+        self.entries.iter().filter(|sym| match sym.scope {
+            SymbolScope::Global | SymbolScope::Static => sym.ident != DebugSymbolEntry::IDENT_FUNCTION,
+            _ => false,
+        }).find(|sym| sym.contains(addr)).cloned()
+    }
+
+    // A local or argument at frame offset |addr|, live at |code_addr|.
+    pub fn find_local(&self, code_addr: i32, addr: i32) -> Option<DebugSymbolEntry> {
+        self.entries.iter().filter(|sym| match sym.scope {
+            SymbolScope::Local | SymbolScope::Arg => (code_addr as u32) >= sym.code_start && (code_addr as u32) < sym.code_end,
+            _ => false,
+        }).find(|sym| sym.contains(addr)).cloned()
+    }
+
+    pub fn entries(&self) -> Vec<DebugSymbolEntry> {
+        self.entries.clone()
+    }
+
+    pub fn entries_ref(&self) -> &Vec<DebugSymbolEntry> {
+        self.entries.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SMXDebugMethods {
     entries: Vec<DebugMethodEntry>,
@@ -567,13 +775,17 @@ impl SMXDebugMethods {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
         let mut rtti = SMXRTTIListTable::new(header, section);
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(DebugMethodEntry::SIZE as u32)?;
+
         let mut entries: Vec<DebugMethodEntry> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             entries.push(DebugMethodEntry::new(&mut data)?)
         }
 
@@ -612,13 +824,17 @@ impl SMXDebugSymbols {
         let base = BaseSection::new(Rc::clone(&header), Rc::clone(&section));
         let mut rtti = SMXRTTIListTable::new(header, section);
 
-        let mut data = Cursor::new(base.get_data());
+        let mut data = Cursor::new(base.get_data_ref());
 
         rtti.init(&mut data)?;
 
+        rtti.check_row_size(DebugVarEntry::SIZE as u32)?;
+
         let mut entries: Vec<DebugVarEntry> = Vec::with_capacity(rtti.row_count() as usize);
 
-        for _ in 0..rtti.row_count() {
+        for i in 0..rtti.row_count() {
+            rtti.seek_row(&mut data, i);
+
             entries.push(DebugVarEntry::new(&mut data)?)
         }
 
@@ -633,7 +849,8 @@ impl SMXDebugSymbols {
             return self
         }
 
-        self.address_sorted.sort_by(|a, b| b.address.cmp(&a.address));
+        self.address_sorted = self.entries.clone();
+        self.address_sorted.sort_by_key(|a| a.address);
 
         self
     }
@@ -727,11 +944,11 @@ impl SMXDebugLocals {
 
             for i in 0..self.file.borrow().debug_methods.as_ref().unwrap().len() {
                 let f = self.file.borrow();
-                let method_index: i32 = f.debug_methods.as_ref().unwrap().entries_ref()[i as usize].method_index;
+                let method_index: i32 = f.debug_methods.as_ref().unwrap().entries_ref()[i].method_index;
                 let method: &RTTIMethod = &f.rtti_methods.as_ref().unwrap().methods_ref()[method_index as usize];
 
                 if code_addr > method.pcode_start && code_addr < method.pcode_end {
-                    index = Some(i as usize);
+                    index = Some(i);
                     break;
                 }
             }
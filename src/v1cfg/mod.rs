@@ -0,0 +1,100 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+use crate::v1disassembler::{V1Instruction, V1Param};
+use crate::v1opcodes::V1OPCode;
+
+// A straight-line run of instructions. |insns| indexes into the slice the
+// block was split from; |successors| are the addresses control can reach
+// next, in ascending order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasicBlock {
+    pub start: i32,
+    pub insns: Range<usize>,
+    pub successors: Vec<i32>,
+}
+
+// Whether |insn| ends a block regardless of what follows it.
+fn is_terminator(insn: &V1Instruction) -> bool {
+    match insn.info.opcode {
+        V1OPCode::RETN | V1OPCode::HALT | V1OPCode::SWITCH | V1OPCode::CASETBL => true,
+        _ => matches!(insn.info.params.first(), Some(V1Param::Jump)),
+    }
+}
+
+// Addresses |insn| transfers control to, ignoring fall through.
+fn branch_targets(insn: &V1Instruction) -> Vec<i32> {
+    match insn.info.opcode {
+        // SWITCH jumps through its CASETBL, which dispatches to the cases.
+        V1OPCode::SWITCH => vec![insn.params[0]],
+        V1OPCode::CASETBL => insn.references(),
+        _ => insn.info.params.iter().enumerate()
+            .filter(|(_, param)| matches!(param, V1Param::Jump))
+            .map(|(i, _)| insn.params[i])
+            .collect(),
+    }
+}
+
+// Whether execution can continue with the next instruction after |insn|.
+fn falls_through(insn: &V1Instruction) -> bool {
+    !matches!(insn.info.opcode, V1OPCode::RETN | V1OPCode::HALT | V1OPCode::JUMP | V1OPCode::SWITCH | V1OPCode::CASETBL)
+}
+
+// Split a function's instructions into basic blocks. Blocks start at the
+// first instruction, at every branch target and after every branch, return
+// or switch.
+pub fn basic_blocks(insns: &[V1Instruction]) -> Vec<BasicBlock> {
+    let mut leaders: BTreeSet<i32> = BTreeSet::new();
+
+    if let Some(first) = insns.first() {
+        leaders.insert(first.address);
+    }
+
+    for (i, insn) in insns.iter().enumerate() {
+        if !is_terminator(insn) {
+            continue;
+        }
+
+        leaders.extend(branch_targets(insn));
+
+        if let Some(next) = insns.get(i + 1) {
+            leaders.insert(next.address);
+        }
+    }
+
+    let mut blocks: Vec<BasicBlock> = Vec::new();
+    let mut start: usize = 0;
+
+    for i in 0..insns.len() {
+        let next = insns.get(i + 1);
+
+        if next.is_some_and(|next| !leaders.contains(&next.address)) {
+            continue;
+        }
+
+        let last = &insns[i];
+
+        let mut successors: Vec<i32> = match is_terminator(last) {
+            true => branch_targets(last),
+            false => Vec::new(),
+        };
+
+        if let Some(next) = next {
+            if falls_through(last) {
+                successors.push(next.address);
+            }
+        }
+
+        successors.sort_unstable();
+        successors.dedup();
+
+        blocks.push(BasicBlock {
+            start: insns[start].address,
+            insns: start..i + 1,
+            successors,
+        });
+
+        start = i + 1;
+    }
+
+    blocks
+}
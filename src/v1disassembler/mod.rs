@@ -1,6 +1,7 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
 use std::io::{Cursor, Seek, SeekFrom};
 use byteorder::{ReadBytesExt, LittleEndian};
 use std::convert::TryFrom;
@@ -33,6 +34,102 @@ pub struct V1Instruction {
     pub params: Vec<i32>,
 }
 
+// A decoded CASETBL: the default target and (value, target) pairs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchTable {
+    pub default: i32,
+    pub cases: Vec<(i32, i32)>,
+}
+
+// Find the case table a SWITCH instruction jumps through. SWITCH's operand is
+// the address of the CASETBL, which the disassembler emits as a separate
+// instruction within the same function.
+pub fn resolve_switch(insns: &[V1Instruction], switch: &V1Instruction) -> Option<SwitchTable> {
+    if switch.info.opcode != V1OPCode::SWITCH {
+        return None
+    }
+
+    let table = insns.iter().find(|insn| insn.address == switch.params[0] && insn.info.opcode == V1OPCode::CASETBL)?;
+
+    table.switch_table()
+}
+
+impl V1Instruction {
+    // The case table of a CASETBL instruction. Its raw params are laid out as
+    // [ncases, default, value0, target0, value1, target1, ...].
+    pub fn switch_table(&self) -> Option<SwitchTable> {
+        if self.info.opcode != V1OPCode::CASETBL {
+            return None
+        }
+
+        let default: i32 = *self.params.get(1)?;
+
+        // The count is taken from the code; trust only the cases present.
+        let count: usize = (self.params[0].max(0) as usize).min((self.params.len() - 2) / 2);
+
+        Some(SwitchTable {
+            default,
+            cases: (0..count).map(|i| (self.params[2 + i * 2], self.params[2 + i * 2 + 1])).collect(),
+        })
+    }
+
+    // Addresses this instruction refers to: jump and call targets, switch
+    // table entries and absolute code/data operands.
+    pub fn references(&self) -> Vec<i32> {
+        match self.info.opcode {
+            V1OPCode::CASETBL => match self.switch_table() {
+                Some(table) => std::iter::once(table.default).chain(table.cases.iter().map(|(_, target)| *target)).collect(),
+                None => Vec::new(),
+            },
+            _ => {
+                let mut targets: Vec<i32> = Vec::new();
+
+                for (i, param) in self.info.params.iter().enumerate() {
+                    match param {
+                        V1Param::Jump | V1Param::Function => targets.push(self.params[i]),
+                        _ if self.info.opcode == V1OPCode::SWITCH => targets.push(self.params[i]),
+                        _ if self.is_data_operand(i) => targets.push(self.params[i]),
+                        _ => (),
+                    }
+                }
+
+                targets
+            },
+        }
+    }
+
+    // Whether the operand at |index| is an absolute data address. The direct
+    // load/store opcodes are listed as constants in the opcode table, but
+    // address global memory all the same.
+    pub fn is_data_operand(&self, index: usize) -> bool {
+        match self.info.opcode {
+            V1OPCode::LOAD_PRI | V1OPCode::LOAD_ALT | V1OPCode::LOAD_BOTH |
+            V1OPCode::STOR_PRI | V1OPCode::STOR_ALT => index < self.params.len(),
+            V1OPCode::CASETBL | V1OPCode::SWITCH => false,
+            _ => matches!(self.info.params.get(index), Some(V1Param::Address)),
+        }
+    }
+}
+
+// Constants in hex, stack offsets and addresses signed, and code targets
+// as-is, e.g. "load.s.pri -12" or "push2.c 0x1 0x2".
+impl fmt::Display for V1Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.info.name)?;
+
+        for (i, value) in self.params.iter().enumerate() {
+            match self.info.params.get(i) {
+                Some(V1Param::Constant) if *value < 0 => write!(f, " -{:#x}", value.unsigned_abs())?,
+                Some(V1Param::Constant) => write!(f, " {:#x}", value)?,
+                Some(V1Param::Stack) | Some(V1Param::Address) => write!(f, " {:+}", value)?,
+                _ => write!(f, " {}", value)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
 lazy_static! {
     static ref OPCODE_LIST: HashMap<u32, V1OPCodeInfo> = {
         let mut m = HashMap::new();
@@ -182,10 +279,37 @@ lazy_static! {
 
         m
     };
+
+    static ref OPCODE_TABLE: Vec<V1OPCodeInfo> = {
+        let mut table: Vec<V1OPCodeInfo> = OPCODE_LIST.values().cloned().collect();
+
+        table.sort_by_key(|info| info.opcode.clone() as u32);
+
+        table
+    };
+}
+
+// Name and operand layout of |op|, as used by the disassembler. None for
+// opcodes it does not decode, such as the ungenerated ones.
+pub fn opcode_info(op: V1OPCode) -> Option<&'static V1OPCodeInfo> {
+    OPCODE_LIST.get(&(op as u32))
+}
+
+// Every opcode the disassembler decodes, in opcode order.
+pub fn all_opcodes() -> &'static [V1OPCodeInfo] {
+    &OPCODE_TABLE
+}
+
+// Look an opcode up by mnemonic, e.g. "load.s.pri". Case is ignored and
+// '_' is accepted in place of '.'.
+pub fn opcode_by_name(name: &str) -> Option<&'static V1OPCodeInfo> {
+    let name: String = name.replace('_', ".").to_lowercase();
+
+    OPCODE_TABLE.iter().find(|info| info.name == name)
 }
 
 pub struct V1Disassembler {
-    file: Rc<RefCell<SMXFile>>,
+    file: Option<Rc<RefCell<SMXFile>>>,
     data: Vec<u8>,
     code_start: i32,
     _proc_offset: i32,
@@ -196,7 +320,7 @@ pub struct V1Disassembler {
 impl V1Disassembler {
     pub fn new(file: Rc<RefCell<SMXFile>>, data: Vec<u8>, code: Rc<SMXCodeV1Section>, proc_offset: i32) -> Self {
         Self {
-            file: Rc::clone(&file),
+            file: Some(Rc::clone(&file)),
             data,
             code_start: code.code_start(),
             _proc_offset: proc_offset,
@@ -205,7 +329,24 @@ impl V1Disassembler {
         }
     }
 
+    // Disassembler over a standalone code blob, without any file context.
+    fn from_bytes(code: &[u8], proc_offset: i32) -> Self {
+        Self {
+            file: None,
+            data: code.to_vec(),
+            code_start: 0,
+            _proc_offset: proc_offset,
+            cursor: proc_offset,
+            cursor_limit: code.len() as i32,
+        }
+    }
+
     fn read_at(&self, offset: i32) -> Result<i32> {
+        // Operands of the last instruction can run past the end of the code.
+        if offset < 0 || offset > self.cursor_limit - 4 {
+            return Err(Error::OffsetOverflow)
+        }
+
         let mut cursor = Cursor::new(&self.data);
 
         cursor.seek(SeekFrom::Start((self.code_start + offset) as u64))?;
@@ -220,7 +361,11 @@ impl V1Disassembler {
     }
 
     fn read_next_op(&mut self) -> Result<V1OPCode> {
-        Ok(V1OPCode::try_from(self.read_next()? as u8).unwrap())
+        let value: i32 = self.read_next()?;
+
+        u8::try_from(value).ok()
+            .and_then(|op| V1OPCode::try_from(op).ok())
+            .ok_or(Error::UnknownOpcode(value as u8))
     }
 
     fn diassemble_internal(&mut self) -> Result<Vec<V1Instruction>> {
@@ -233,21 +378,29 @@ impl V1Disassembler {
         while self.cursor < self.cursor_limit {
             let address: i32 = self.cursor;
 
-            let op: i32 = self.read_next()?;
+            let op: V1OPCode = self.read_next_op()?;
 
-            if op == V1OPCode::PROC as i32 || op == V1OPCode::ENDPROC as i32 {
+            if op == V1OPCode::PROC || op == V1OPCode::ENDPROC {
                 break;
             }
 
+            let info = OPCODE_LIST.get(&(op.clone() as u32)).ok_or(Error::UnknownOpcode(op.clone() as u8))?;
+
             let mut insn: V1Instruction = V1Instruction {
                 address,
-                info: OPCODE_LIST.get(&(op as u32)).unwrap().clone(),
+                info: info.clone(),
                 params: Vec::new(),
             };
 
-            if op == V1OPCode::CASETBL as i32 {
+            if op == V1OPCode::CASETBL {
                 let ncases: i32 = self.read_next()?;
 
+                // Each case takes two cells; don't size the table from a
+                // count the code can't hold.
+                if ncases < 0 || ncases > (self.cursor_limit - self.cursor) / 8 {
+                    return Err(Error::OffsetOverflow)
+                }
+
                 insn.params.resize(((ncases + 1) * 2) as usize, 0);
 
                 insn.params[0] = ncases;
@@ -269,11 +422,13 @@ impl V1Disassembler {
                 insn.params[i] = self.read_next()?;
             }
 
-            if op == V1OPCode::CALL as i32 {
-                let addr: i32 = insn.params[0];
+            if op == V1OPCode::CALL {
+                if let Some(file) = &self.file {
+                    let addr: i32 = insn.params[0];
 
-                if !self.file.borrow().is_function_at_address(addr) {
-                    self.file.borrow_mut().called_functions.as_mut().unwrap().borrow_mut().add_function(addr as u32);
+                    if !file.borrow().is_discovered_function(addr) {
+                        file.borrow_mut().add_called_function(addr as u32);
+                    }
                 }
             }
 
@@ -288,4 +443,12 @@ impl V1Disassembler {
 
         disassembler.diassemble_internal()
     }
+
+    // Disassemble a function from a raw code buffer. Function and native
+    // resolution is unavailable since there is no backing file.
+    pub fn disassemble_bytes(code: &[u8], start: i32) -> Result<Vec<V1Instruction>> {
+        let mut disassembler: V1Disassembler = V1Disassembler::from_bytes(code, start);
+
+        disassembler.diassemble_internal()
+    }
 }
\ No newline at end of file
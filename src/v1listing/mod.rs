@@ -0,0 +1,365 @@
+use crate::file::SMXFile;
+use crate::v1disassembler::{V1Instruction, V1Param, resolve_switch};
+use crate::v1opcodes::V1OPCode;
+
+// Column at which trailing comments start.
+const COMMENT_COLUMN: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Radix {
+    Hex,
+    Decimal,
+}
+
+// Formatting toggles for listings.
+#[derive(Debug, Clone)]
+pub struct DisassemblyOptions {
+    // Base used for numeric operands.
+    pub radix: Radix,
+
+    // Render stack operands as cell indexes rather than byte offsets.
+    pub cell_offsets: bool,
+
+    // Comment operands with the names of the variables they refer to.
+    pub annotate_symbols: bool,
+
+    // Comment instructions with their source file and line.
+    pub annotate_lines: bool,
+
+    // Render constants stored into float variables as floats.
+    pub float_constants: bool,
+
+    // Also print each address as an offset from this function start.
+    pub relative_to: Option<i32>,
+
+    // Replace jump targets with labels, printed above the instructions they
+    // name.
+    pub jump_labels: bool,
+
+    // Print native and function operands by name. Functions discovered
+    // only through calls are named sub_<address>.
+    pub resolve_names: bool,
+
+    // Comment constants that point at a printable string in .data with the
+    // string itself.
+    pub string_literals: bool,
+}
+
+// The original name of DisassemblyOptions.
+pub type DisasmOptions = DisassemblyOptions;
+
+impl Default for DisassemblyOptions {
+    fn default() -> Self {
+        Self {
+            radix: Radix::Hex,
+            cell_offsets: false,
+            annotate_symbols: true,
+            annotate_lines: true,
+            float_constants: false,
+            relative_to: None,
+            jump_labels: true,
+            resolve_names: true,
+            string_literals: true,
+        }
+    }
+}
+
+// Chainable setters, starting from the defaults.
+impl DisassemblyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn radix(mut self, radix: Radix) -> Self {
+        self.radix = radix;
+        self
+    }
+
+    pub fn cell_offsets(mut self, enabled: bool) -> Self {
+        self.cell_offsets = enabled;
+        self
+    }
+
+    pub fn annotate_symbols(mut self, enabled: bool) -> Self {
+        self.annotate_symbols = enabled;
+        self
+    }
+
+    pub fn annotate_lines(mut self, enabled: bool) -> Self {
+        self.annotate_lines = enabled;
+        self
+    }
+
+    pub fn float_constants(mut self, enabled: bool) -> Self {
+        self.float_constants = enabled;
+        self
+    }
+
+    pub fn relative_to(mut self, addr: Option<i32>) -> Self {
+        self.relative_to = addr;
+        self
+    }
+
+    pub fn jump_labels(mut self, enabled: bool) -> Self {
+        self.jump_labels = enabled;
+        self
+    }
+
+    pub fn resolve_names(mut self, enabled: bool) -> Self {
+        self.resolve_names = enabled;
+        self
+    }
+
+    pub fn string_literals(mut self, enabled: bool) -> Self {
+        self.string_literals = enabled;
+        self
+    }
+}
+
+fn format_number(value: i32, radix: Radix) -> String {
+    match radix {
+        Radix::Hex if value < 0 => format!("-{:#x}", value.unsigned_abs()),
+        Radix::Hex => format!("{:#x}", value),
+        Radix::Decimal => format!("{}", value),
+    }
+}
+
+pub(crate) fn format_address(addr: i32, options: &DisassemblyOptions) -> String {
+    match options.relative_to {
+        Some(start) => format!("{:#010x} +{:#06x}", addr, addr - start),
+        None => format!("{:#010x}", addr),
+    }
+}
+
+fn format_operand(insn: &V1Instruction, index: usize, options: &DisassemblyOptions) -> String {
+    let value: i32 = insn.params[index];
+
+    match insn.info.params.get(index) {
+        Some(V1Param::Stack) if options.cell_offsets => format_number(value / 4, options.radix),
+        _ => format_number(value, options.radix),
+    }
+}
+
+fn format_label(addr: i32) -> String {
+    format!("L_{:#x}", addr)
+}
+
+// Targets of every jump operand in |insns|, in address order.
+pub fn jump_targets(insns: &[V1Instruction]) -> Vec<i32> {
+    let mut targets: Vec<i32> = Vec::new();
+
+    for insn in insns {
+        for (i, param) in insn.info.params.iter().enumerate() {
+            if let V1Param::Jump = param {
+                targets.push(insn.params[i]);
+            }
+        }
+    }
+
+    targets.sort_unstable();
+    targets.dedup();
+
+    targets
+}
+
+// The name an operand refers to, if it names a native or a function.
+fn operand_name(file: &SMXFile, insn: &V1Instruction, index: usize) -> Option<String> {
+    let value: i32 = insn.params[index];
+
+    match insn.info.params.get(index) {
+        Some(V1Param::Native) => match &file.natives {
+            Some(natives) if value >= 0 && (value as usize) < natives.size() => Some(natives.get_entry(value as usize).name),
+            _ => None,
+        },
+        Some(V1Param::Function) => file.find_function_name(value),
+        _ => None,
+    }
+}
+
+fn format_instruction(file: &SMXFile, insn: &V1Instruction, options: &DisassemblyOptions, float_operand: Option<usize>) -> String {
+    let mut text: String = insn.info.name.clone();
+
+    for i in 0..insn.params.len() {
+        text += " ";
+
+        let name: Option<String> = match options.resolve_names {
+            true => operand_name(file, insn, i),
+            false => None,
+        };
+
+        if let Some(name) = name {
+            text += &name;
+        } else if float_operand == Some(i) {
+            text += &format_float(insn.params[i]);
+        } else if options.jump_labels && matches!(insn.info.params.get(i), Some(V1Param::Jump)) {
+            text += &format_label(insn.params[i]);
+        } else {
+            text += &format_operand(insn, i, options);
+        }
+    }
+
+    text
+}
+
+// Render a cell holding float bits as a float literal, e.g. 0x3fc00000 as
+// "1.5f", so it cannot be mistaken for an integer operand.
+pub fn format_float(value: i32) -> String {
+    format!("{:?}f", f32::from_bits(value as u32))
+}
+
+fn is_float_type(name: &str) -> bool {
+    let name = name.trim_start_matches("const ");
+
+    name == "float" || name.starts_with("float[")
+}
+
+// Find the constant operand of |insns[index]| that ends up in a float
+// variable, either directly through CONST/CONST_S or through a register that
+// the next instruction stores. Without debug type info nothing is a float.
+fn float_constant_operand(file: &SMXFile, insns: &[V1Instruction], index: usize) -> Option<usize> {
+    let insn = &insns[index];
+
+    let next = || insns[index + 1..].iter().find(|i| i.info.opcode != V1OPCode::BREAK);
+
+    let (operand, dest) = match insn.info.opcode {
+        V1OPCode::CONST => (1, file.find_global_type(insn.params[0])),
+        V1OPCode::CONST_S => (1, file.find_local_type(insn.address, insn.params[0])),
+        V1OPCode::CONST_PRI | V1OPCode::CONST_ALT => {
+            let store = next()?;
+
+            let is_pri = insn.info.opcode == V1OPCode::CONST_PRI;
+
+            let dest = match store.info.opcode {
+                V1OPCode::STOR_PRI if is_pri => file.find_global_type(store.params[0]),
+                V1OPCode::STOR_ALT if !is_pri => file.find_global_type(store.params[0]),
+                V1OPCode::STOR_S_PRI if is_pri => file.find_local_type(store.address, store.params[0]),
+                V1OPCode::STOR_S_ALT if !is_pri => file.find_local_type(store.address, store.params[0]),
+                _ => Ok(None),
+            };
+
+            (0, dest)
+        },
+        _ => return None,
+    };
+
+    // Undecodable types are treated as unknown; the listing stays integral.
+    match dest {
+        Ok(Some(name)) if is_float_type(&name) => Some(operand),
+        _ => None,
+    }
+}
+
+// Names of the variables referenced by an instruction's operands.
+fn symbol_comments(file: &SMXFile, insn: &V1Instruction) -> Vec<String> {
+    let mut comments: Vec<String> = Vec::new();
+
+    if insn.info.opcode == V1OPCode::CASETBL {
+        return comments;
+    }
+
+    for (i, param) in insn.info.params.iter().enumerate() {
+        let name: Option<String> = match param {
+            V1Param::Stack => file.find_local_name(insn.address, insn.params[i]),
+            _ if insn.is_data_operand(i) => file.find_global_name(insn.params[i]),
+            _ => None,
+        };
+
+        if let Some(name) = name {
+            comments.push(name);
+        }
+    }
+
+    comments
+}
+
+// The string a constant load points at, if any. Literals are cell aligned in
+// .data; anything empty or holding control or invalid UTF-8 bytes is taken to
+// be binary data rather than text.
+fn string_literal(file: &SMXFile, insn: &V1Instruction) -> Option<String> {
+    let value: i32 = match insn.info.opcode {
+        V1OPCode::CONST_PRI | V1OPCode::CONST_ALT => insn.params[0],
+        V1OPCode::CONST => insn.params[1],
+        _ => return None,
+    };
+
+    if value < 0 || value % 4 != 0 {
+        return None;
+    }
+
+    let s = file.data.as_ref()?.string_at(value as u32).ok()?;
+
+    if s.is_empty() || s.chars().any(|c| c.is_control() || c == char::REPLACEMENT_CHARACTER) {
+        return None;
+    }
+
+    Some(format!("{:?}", s))
+}
+
+// Render a disassembled function, one instruction per line. When the file
+// carries debug info, each line whose source position differs from the
+// previous instruction is annotated with its file and line. SWITCH
+// instructions are followed by the cases of their table, and with
+// |jump_labels| set, jump targets get a label line of their own.
+pub fn function_listing(file: &SMXFile, insns: &[V1Instruction], options: &DisassemblyOptions) -> String {
+    let mut listing: String = String::new();
+    let mut last_location: Option<(String, u32)> = None;
+
+    let labels: Vec<i32> = match options.jump_labels {
+        true => jump_targets(insns),
+        false => Vec::new(),
+    };
+
+    for (i, insn) in insns.iter().enumerate() {
+        if labels.binary_search(&insn.address).is_ok() {
+            listing += &format!("{}:\n", format_label(insn.address));
+        }
+
+        let float_operand: Option<usize> = match options.float_constants {
+            true => float_constant_operand(file, insns, i),
+            false => None,
+        };
+
+        let line: String = format!("{}: {}", format_address(insn.address, options), format_instruction(file, insn, options, float_operand));
+
+        let mut comments: Vec<String> = Vec::new();
+
+        if options.annotate_symbols {
+            comments.extend(symbol_comments(file, insn));
+        }
+
+        if options.string_literals {
+            comments.extend(string_literal(file, insn));
+        }
+
+        if options.annotate_lines {
+            let location = file.source_location(insn.address as u32);
+
+            if let Some((name, number)) = &location {
+                if location != last_location {
+                    comments.push(format!("{}:{}", name, number));
+                }
+            }
+
+            last_location = location;
+        }
+
+        if comments.is_empty() {
+            listing += &line;
+        } else {
+            listing += &format!("{:<width$}; {}", line, comments.join("; "), width = COMMENT_COLUMN);
+        }
+
+        listing += "\n";
+
+        // Inline the case table under its SWITCH.
+        if let Some(table) = resolve_switch(insns, insn) {
+            for (value, target) in &table.cases {
+                listing += &format!("{:12}case {}: {}\n", "", format_number(*value, options.radix), format_number(*target, options.radix));
+            }
+
+            listing += &format!("{:12}default: {}\n", "", format_number(table.default, options.radix));
+        }
+    }
+
+    listing
+}
@@ -2,7 +2,7 @@ use std::fmt::{Display, Formatter, Result};
 use num_enum::TryFromPrimitive;
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, PartialEq, TryFromPrimitive)]
+#[derive(Debug, Clone, PartialEq, Default, TryFromPrimitive)]
 #[repr(u8)]
 pub enum V1OPCode {
     NONE,
@@ -192,13 +192,10 @@ pub enum V1OPCode {
     FLOAT_NE, 
     FLOAT_EQ, 
     FLOAT_NOT,
+    #[default]
     TOTAL_OPCODES
 }
 
-impl Default for V1OPCode {
-    fn default() -> Self { V1OPCode::TOTAL_OPCODES }
-}
-
 impl Display for V1OPCode {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "{:?}", self)
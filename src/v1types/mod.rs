@@ -3,7 +3,7 @@ use std::cell::RefCell;
 use std::fmt;
 use std::io::{Cursor};
 use byteorder::{ReadBytesExt, LittleEndian};
-use crate::headers::{SectionEntry};
+use crate::headers::{SectionEntry, SMXHeader};
 use crate::sections::{SMXNameTable};
 use crate::errors::{Result, Error};
 
@@ -13,6 +13,20 @@ bitflags! {
     }
 }
 
+// Feature bits of a version 13+ code header.
+bitflags! {
+    pub struct CodeFeatures: u32 {
+        const DEPRECATED0 = SMXHeader::K_CODE_FEATURE_DEPRECATED_0;
+
+        // INIT_ARRAY, and direct internal addressing for multi-dimensional
+        // arrays.
+        const DIRECT_ARRAYS = SMXHeader::K_CODE_FEATURE_DIRECT_ARRAYS;
+
+        // HEAP_SAVE/HEAP_RESTORE scoping of heap allocations.
+        const HEAP_SCOPES = SMXHeader::K_CODE_FEATURE_HEAP_SCOPES;
+    }
+}
+
 // The ".code" section.
 #[derive(Debug, Clone)]
 pub struct CodeV1Header {
@@ -56,6 +70,13 @@ impl CodeV1Header {
         let code_size = cursor.read_i32::<LittleEndian>()?;
         let cell_size = cursor.read_u8()?;
         let code_version = cursor.read_u8()?;
+
+        // Anything outside this range has an instruction encoding we can't
+        // disassemble.
+        if !(SMXHeader::CODE_VERSION_MINIMUM..=SMXHeader::CODE_VERSION_CURRENT).contains(&code_version) {
+            return Err(Error::UnsupportedCodeVersion(code_version))
+        }
+
         let flags = cursor.read_u16::<LittleEndian>()?;
         let main_offset = cursor.read_i32::<LittleEndian>()?;
         let code_offset = cursor.read_i32::<LittleEndian>()?;
@@ -68,14 +89,18 @@ impl CodeV1Header {
             main_offset,
             code_offset,
             features: {
-                if code_version >= 13 {
-                    cursor.read_i32::<LittleEndian>()?;
+                if code_version >= SMXHeader::CODE_VERSION_FEATURE_MASK {
+                    cursor.read_i32::<LittleEndian>()?
+                } else {
+                    0
                 }
-
-                0
             }
         })
     }
+
+    pub fn features_set(&self) -> CodeFeatures {
+        CodeFeatures::from_bits_truncate(self.features as u32)
+    }
 }
 
 // The ".data" section.
@@ -108,8 +133,27 @@ impl DataHeader {
     }
 }
 
+// Runtime memory layout of a plugin. Initialized data occupies the bottom of
+// memory, the heap grows upward from the end of it, and the stack grows
+// downward from the top of |memory_size|.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryLayout {
+    // Start of the data region.
+    pub data_start: u32,
+
+    // End (exclusive) of the data region.
+    pub data_end: u32,
+
+    // Where the heap begins.
+    pub heap_start: u32,
+
+    // Initial top of the stack.
+    pub stack_top: u32,
+}
+
 // The ".publics" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PublicEntry {
     // Offset into the code section.
     pub address: u32,
@@ -128,7 +172,7 @@ impl PublicEntry {
     where
         T: AsRef<[u8]>,
     {
-        if section.size & Self::SIZE != 0 {
+        if section.size % Self::SIZE != 0 {
             return Err(Error::InvalidSize)
         }
 
@@ -162,6 +206,7 @@ pub struct CalledFunctionEntry {
 
 // The ".natives" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct NativeEntry {
     // Offset into the .names section.
     pub name_offset: i32,
@@ -202,6 +247,7 @@ impl NativeEntry {
 
 // The ".pubvars" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PubvarEntry {
     // Offset into the data section.
     pub address: u32,
@@ -247,6 +293,7 @@ impl PubvarEntry {
 
 // The ".tags" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TagEntry {
     // Tag ID from the compiler.
     pub tag: u32,
@@ -280,11 +327,11 @@ impl TagEntry {
     where
         T: AsRef<[u8]>,
     {
-        if section.size % NativeEntry::SIZE != 0 {
+        if section.size % Self::SIZE != 0 {
             return Err(Error::InvalidSize)
         }
 
-        let count: usize = (section.size / NativeEntry::SIZE) as usize;
+        let count: usize = (section.size / Self::SIZE) as usize;
 
         let mut entries: Vec<Self> = Vec::with_capacity(count);
 
@@ -335,6 +382,7 @@ impl DebugInfoHeader {
 
 // The ".dbg.files" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DebugFileEntry {
     // Offset into the data section.
     pub address: u32,
@@ -380,6 +428,7 @@ impl DebugFileEntry {
 
 // The ".dbg.lines" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DebugLineEntry {
     // Offset into the data section.
     pub address: u32,
@@ -420,6 +469,7 @@ impl DebugLineEntry {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum SymbolScope {
     Global,
     Local,
@@ -461,7 +511,9 @@ pub struct DebugMethodEntry {
 }
 
 impl DebugMethodEntry {
-    pub fn new(cursor: &mut Cursor<Vec<u8>>) -> Result<Self> {
+    pub const SIZE: i32 = 8;
+
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> Result<Self> {
         Ok(Self {
             method_index: cursor.read_i32::<LittleEndian>()?,
             first_local: cursor.read_i32::<LittleEndian>()?,
@@ -469,8 +521,200 @@ impl DebugMethodEntry {
     }
 }
 
+// An array dimension trailing a legacy debug record.
+#[derive(Debug, Clone)]
+pub struct DebugArrayDim {
+    pub tag_id: i16,
+
+    pub size: u32,
+}
+
+impl DebugArrayDim {
+    pub const SIZE: i32 = 6;
+
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> Result<Self> {
+        Ok(Self {
+            tag_id: cursor.read_i16::<LittleEndian>()?,
+            size: cursor.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+// An argument of a ".dbg.natives" entry.
+#[derive(Debug, Clone)]
+pub struct DebugNativeArgEntry {
+    // Variable kind, as in legacy symbols (1 = variable, 2 = reference,
+    // 3 = array, 4 = reference array, 11 = varargs).
+    pub ident: u8,
+
+    pub tag_id: i16,
+
+    // Offset into the debug names table.
+    pub name_offset: i32,
+
+    // Computed name.
+    pub name: String,
+
+    pub dims: Vec<DebugArrayDim>,
+}
+
+// The ".dbg.natives" section. Legacy plugins describe native prototypes here
+// instead of in rtti.natives.
+#[derive(Debug, Clone)]
+pub struct DebugNativeEntry {
+    // Index into the .natives table.
+    pub index: i32,
+
+    // Offset into the debug names table.
+    pub name_offset: i32,
+
+    // Computed name.
+    pub name: String,
+
+    // Return tag.
+    pub tag_id: i16,
+
+    pub args: Vec<DebugNativeArgEntry>,
+}
+
+impl DebugNativeEntry {
+    pub const SIZE: i32 = 12;
+
+    pub const ARG_SIZE: i32 = 9;
+
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, names: &Rc<RefCell<SMXNameTable>>) -> Result<Self> {
+        let index = cursor.read_i32::<LittleEndian>()?;
+        let name_offset = cursor.read_i32::<LittleEndian>()?;
+        let tag_id = cursor.read_i16::<LittleEndian>()?;
+        let arg_count = cursor.read_u16::<LittleEndian>()?;
+
+        let mut args: Vec<DebugNativeArgEntry> = Vec::with_capacity(arg_count as usize);
+
+        for _ in 0..arg_count {
+            let ident = cursor.read_u8()?;
+            let tag_id = cursor.read_i16::<LittleEndian>()?;
+            let dim_count = cursor.read_u16::<LittleEndian>()?;
+            let name_offset = cursor.read_i32::<LittleEndian>()?;
+
+            let mut dims: Vec<DebugArrayDim> = Vec::with_capacity(dim_count as usize);
+
+            for _ in 0..dim_count {
+                dims.push(DebugArrayDim::new(cursor)?);
+            }
+
+            args.push(DebugNativeArgEntry {
+                ident,
+                tag_id,
+                name_offset,
+                name: names.borrow_mut().string_at(name_offset)?,
+                dims,
+            });
+        }
+
+        Ok(Self {
+            index,
+            name_offset,
+            name: names.borrow_mut().string_at(name_offset)?,
+            tag_id,
+            args,
+        })
+    }
+}
+
+// The legacy ".dbg.symbols" section, which predates .dbg.globals and
+// .dbg.locals.
+#[derive(Debug, Clone)]
+pub struct DebugSymbolEntry {
+    // Data address for globals, frame offset for locals and arguments, code
+    // address for functions.
+    pub address: i32,
+
+    pub tag_id: i16,
+
+    // Code range the symbol is live in.
+    pub code_start: u32,
+
+    pub code_end: u32,
+
+    // Symbol kind (1 = variable, 2 = reference, 3 = array, 4 = reference
+    // array, 9 = function).
+    pub ident: u8,
+
+    pub scope: SymbolScope,
+
+    // Offset into the debug names table.
+    pub name_offset: i32,
+
+    // Computed name.
+    pub name: String,
+
+    pub dims: Vec<DebugArrayDim>,
+}
+
+impl DebugSymbolEntry {
+    pub const IDENT_FUNCTION: u8 = 9;
+
+    // |padded| selects the SP 1.0 layout, where the compiler did not pack the
+    // structs and a 16-bit tag is followed by two bytes of padding.
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>, padded: bool, names: &Rc<RefCell<SMXNameTable>>) -> Result<Self> {
+        let address = cursor.read_i32::<LittleEndian>()?;
+        let tag_id = cursor.read_i16::<LittleEndian>()?;
+
+        if padded {
+            cursor.read_u16::<LittleEndian>()?;
+        }
+
+        let code_start = cursor.read_u32::<LittleEndian>()?;
+        let code_end = cursor.read_u32::<LittleEndian>()?;
+        let ident = cursor.read_u8()?;
+        let scope = SymbolScope::from(cursor.read_u8()?);
+        let dim_count = cursor.read_u16::<LittleEndian>()?;
+        let name_offset = cursor.read_i32::<LittleEndian>()?;
+
+        let mut dims: Vec<DebugArrayDim> = Vec::with_capacity(dim_count as usize);
+
+        for _ in 0..dim_count {
+            let tag_id = cursor.read_i16::<LittleEndian>()?;
+
+            if padded {
+                cursor.read_u16::<LittleEndian>()?;
+            }
+
+            dims.push(DebugArrayDim {
+                tag_id,
+                size: cursor.read_u32::<LittleEndian>()?,
+            });
+        }
+
+        Ok(Self {
+            address,
+            tag_id,
+            code_start,
+            code_end,
+            ident,
+            scope,
+            name_offset,
+            name: names.borrow_mut().string_at(name_offset)?,
+            dims,
+        })
+    }
+
+    // Whether |addr| falls within the symbol's storage. Arrays cover one
+    // cell per element of their dimensions.
+    pub fn contains(&self, addr: i32) -> bool {
+        if self.dims.is_empty() {
+            return addr == self.address
+        }
+
+        let cells: i64 = self.dims.iter().map(|dim| dim.size.max(1) as i64).product();
+
+        addr as i64 >= self.address as i64 && (addr as i64) < self.address as i64 + cells * 4
+    }
+}
+
 // The ".dbg.globals"  and ".dbg.locals" section.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DebugVarEntry {
     pub address: i32,
 
@@ -486,7 +730,9 @@ pub struct DebugVarEntry {
 }
 
 impl DebugVarEntry {
-    pub fn new(cursor: &mut Cursor<Vec<u8>>) -> Result<Self>
+    pub const SIZE: i32 = 21;
+
+    pub fn new<T: AsRef<[u8]>>(cursor: &mut Cursor<T>) -> Result<Self>
     {
         Ok(Self {
             address: cursor.read_i32::<LittleEndian>()?,
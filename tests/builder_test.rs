@@ -0,0 +1,405 @@
+extern crate smxdasm;
+
+use smxdasm::builder::SmxBuilder;
+use smxdasm::file::SMXFile;
+use smxdasm::headers::CompressionType;
+use smxdasm::v1opcodes::V1OPCode;
+
+fn fixture(compression: CompressionType) -> Vec<u8> {
+    SmxBuilder::new()
+        .compression(compression)
+        .code(&[
+            V1OPCode::PROC as i32,
+            V1OPCode::SYSREQ_N as i32, 0, 0,
+            V1OPCode::RETN as i32,
+            V1OPCode::PROC as i32,
+            V1OPCode::ZERO_PRI as i32,
+            V1OPCode::RETN as i32,
+        ])
+        .data(&[5, 0, 0, 0], 0x1000)
+        .natives(&["PrintToServer"])
+        .publics(&[("OnPluginStart", 0), ("OnPluginEnd", 20)])
+        .pubvars(&[("g_Version", 0)])
+        .build()
+}
+
+#[test]
+fn test_builder_round_trip() {
+    for compression in [CompressionType::CompressionNone, CompressionType::CompressionGZ] {
+        let p = SMXFile::new(fixture(compression)).unwrap();
+
+        let f = p.borrow();
+
+        assert!(f.unknown_sections.is_empty());
+        assert_eq!(f.natives.as_ref().unwrap().get_entry(0).name, "PrintToServer");
+        assert_eq!(f.publics.as_ref().unwrap().get_entry(1).name, "OnPluginEnd");
+        assert_eq!(f.pubvar_values().unwrap(), vec![("g_Version".to_string(), 5)]);
+        assert_eq!(f.data.as_ref().unwrap().memory_layout().stack_top, 0x1000);
+
+        assert_eq!(f.functions.len(), 2);
+        assert_eq!(f.referenced_natives().len(), 1);
+    }
+}
+
+#[test]
+fn test_builder_compression() {
+    let plain = fixture(CompressionType::CompressionNone);
+    let packed = fixture(CompressionType::CompressionGZ);
+
+    // The header and section table are shared; only the body is compressed.
+    assert_eq!(&plain[..6], &packed[..6]);
+    assert_eq!(plain[6], 0);
+    assert_eq!(packed[6], 1);
+    assert_eq!(&plain[11..15], &packed[11..15]);
+}
+
+#[test]
+fn test_tags_section() {
+    let mut builder = SmxBuilder::new();
+
+    let mut section: Vec<u8> = Vec::new();
+
+    for (tag, name) in [(0u32, "_"), (1u32, "Float")] {
+        let offset = builder.add_name(name);
+
+        section.extend(&tag.to_le_bytes());
+        section.extend(&offset.to_le_bytes());
+    }
+
+    let p = SMXFile::new(builder.section(".tags", section).build()).unwrap();
+
+    let tags = p.borrow().tags.as_ref().unwrap().entries();
+
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags[1].name(), "Float");
+}
+
+#[test]
+fn test_misaligned_section_size() {
+    use smxdasm::errors::Error;
+
+    // Six bytes is not a whole number of entries for any of these tables.
+    for name in [".publics", ".natives", ".pubvars", ".tags", ".dbg.files", ".dbg.lines"] {
+        let data = SmxBuilder::new()
+            .section(name, vec![0; 6])
+            .build();
+
+        match SMXFile::new(data) {
+            Err(Error::InvalidSize) => {},
+            Err(err) => panic!("{}: expected InvalidSize, got {}", name, err),
+            Ok(_) => panic!("{}: expected InvalidSize", name),
+        }
+    }
+}
+
+#[test]
+fn test_unsupported_code_version() {
+    use smxdasm::errors::Error;
+
+    for version in [8u8, 14, 0x7f] {
+        // code_size, cell_size, code_version, flags, main_offset, code_offset
+        let mut code: Vec<u8> = Vec::new();
+
+        code.extend(&4i32.to_le_bytes());
+        code.push(4);
+        code.push(version);
+        code.extend(&0u16.to_le_bytes());
+        code.extend(&0i32.to_le_bytes());
+        code.extend(&16i32.to_le_bytes());
+        code.extend(&(V1OPCode::PROC as i32).to_le_bytes());
+
+        let data = SmxBuilder::new()
+            .section(".code", code)
+            .build();
+
+        assert!(matches!(SMXFile::new(data), Err(Error::UnsupportedCodeVersion(v)) if v == version));
+    }
+}
+
+#[test]
+fn test_find_tag_shared() {
+    use std::rc::Rc;
+    use smxdasm::sections::SMXTagTable;
+    use smxdasm::v1types::TagEntry;
+
+    let mut builder = SmxBuilder::new();
+
+    let mut section: Vec<u8> = Vec::new();
+
+    for (tag, name) in [(0u32, "_"), (1 | TagEntry::ENUM, "Action"), (2, "Float")] {
+        let offset = builder.add_name(name);
+
+        section.extend(&tag.to_le_bytes());
+        section.extend(&offset.to_le_bytes());
+    }
+
+    let p = SMXFile::new(builder.section(".tags", section).build()).unwrap();
+
+    let tags: Rc<SMXTagTable> = Rc::clone(p.borrow().tags.as_ref().unwrap());
+
+    // Lookups are by id, with the flag bits masked off.
+    let action = tags.find_tag(1).unwrap();
+
+    assert_eq!(action.name(), "Action");
+    assert_eq!(action.flags(), TagEntry::ENUM);
+
+    assert_eq!(tags.find_tag(2).unwrap().name(), "Float");
+    assert!(tags.find_tag(3).is_none());
+}
+
+#[test]
+fn test_name_table_iter() {
+    let p = SMXFile::new(fixture(CompressionType::CompressionNone)).unwrap();
+
+    let names: Vec<(i32, String)> = p.borrow().names.as_ref().unwrap().borrow_mut().iter().collect();
+
+    assert_eq!(names[0], (0, String::new()));
+    assert_eq!(names[1], (1, "PrintToServer".to_string()));
+    assert!(names.iter().any(|(_, name)| name == "g_Version"));
+
+    // An empty table has no strings at all, not even the empty one.
+    let p = SMXFile::new(SmxBuilder::new().section(".names", Vec::new()).build()).unwrap();
+
+    assert_eq!(p.borrow().names.as_ref().unwrap().borrow_mut().iter().count(), 0);
+}
+
+#[test]
+fn test_string_at_checked() {
+    use smxdasm::errors::Error;
+
+    // "ok", invalid UTF-8, then a name that runs off the end of the section.
+    let names = b"ok\0\xff\xfe\0tail".to_vec();
+
+    let p = SMXFile::new(SmxBuilder::new().section(".names", names).build()).unwrap();
+
+    let f = p.borrow();
+    let mut table = f.names.as_ref().unwrap().borrow_mut();
+
+    assert_eq!(table.string_at_checked(0).unwrap(), "ok");
+    assert!(matches!(table.string_at_checked(3), Err(Error::InvalidEncoding)));
+    assert!(matches!(table.string_at_checked(6), Err(Error::Other(_))));
+    assert!(matches!(table.string_at_checked(10), Err(Error::InvalidIndex)));
+
+    // The lenient lookup still decodes whatever is there.
+    assert_eq!(table.string_at(6).unwrap(), "tail");
+    assert_eq!(table.string_at(3).unwrap(), "\u{fffd}\u{fffd}");
+}
+
+#[test]
+fn test_code_features() {
+    use smxdasm::headers::SMXHeader;
+    use smxdasm::v1types::CodeFeatures;
+
+    let features = SMXHeader::K_CODE_FEATURE_DIRECT_ARRAYS | SMXHeader::K_CODE_FEATURE_HEAP_SCOPES;
+
+    // code_size, cell_size, code_version, flags, main_offset, code_offset,
+    // features
+    let mut code: Vec<u8> = Vec::new();
+
+    code.extend(&8i32.to_le_bytes());
+    code.push(4);
+    code.push(SMXHeader::CODE_VERSION_FEATURE_MASK);
+    code.extend(&0u16.to_le_bytes());
+    code.extend(&0i32.to_le_bytes());
+    code.extend(&20i32.to_le_bytes());
+    code.extend(&features.to_le_bytes());
+    code.extend(&(V1OPCode::PROC as i32).to_le_bytes());
+    code.extend(&(V1OPCode::RETN as i32).to_le_bytes());
+
+    let p = SMXFile::new(SmxBuilder::new().section(".code", code).build()).unwrap();
+
+    let header = p.borrow().codev1.as_ref().unwrap().header();
+
+    assert_eq!(header.features as u32, features);
+    assert_eq!(header.features_set(), CodeFeatures::DIRECT_ARRAYS | CodeFeatures::HEAP_SCOPES);
+    assert!(!header.features_set().contains(CodeFeatures::DEPRECATED0));
+}
+
+#[test]
+fn test_pubvar_value() {
+    use smxdasm::errors::Error;
+
+    let p = SMXFile::new(fixture(CompressionType::CompressionNone)).unwrap();
+    let f = p.borrow();
+
+    let version = f.pubvars.as_ref().unwrap().entries().remove(0);
+
+    assert_eq!(f.pubvar_value(&version).unwrap(), 5);
+    assert_eq!(f.pubvar_float(&version).unwrap().to_bits(), 5);
+
+    // Without a data section there is nothing to read from.
+    let p = SMXFile::new(SmxBuilder::new().pubvars(&[("g_Version", 0)]).build()).unwrap();
+    let f = p.borrow();
+
+    let version = f.pubvars.as_ref().unwrap().entries().remove(0);
+
+    assert!(matches!(f.pubvar_value(&version), Err(Error::MissingSection(".data"))));
+    assert!(matches!(f.pubvar_float(&version), Err(Error::MissingSection(".data"))));
+}
+
+#[test]
+fn test_rtti_row_count_overflow() {
+    use smxdasm::errors::Error;
+
+    // header_size, row_size, row_count.
+    for (row_size, row_count) in [(16u32, 0x4000_0000u32), (0, u32::MAX), (16, 2)] {
+        let mut table: Vec<u8> = Vec::new();
+
+        for value in [12u32, row_size, row_count] {
+            table.extend(&value.to_le_bytes());
+        }
+
+        table.extend(&[0; 16]);
+
+        let data = SmxBuilder::new().section("rtti.enums", table).build();
+
+        assert!(matches!(SMXFile::new(data), Err(Error::SizeOverflow)), "{} rows of {}", row_count, row_size);
+    }
+}
+
+fn rtti_table(header_size: u32, row_size: u32, rows: &[Vec<i32>]) -> Vec<u8> {
+    let mut table: Vec<u8> = Vec::new();
+
+    for value in [header_size, row_size, rows.len() as u32] {
+        table.extend(&value.to_le_bytes());
+    }
+
+    for row in rows {
+        let start = table.len();
+
+        for cell in row {
+            table.extend(&cell.to_le_bytes());
+        }
+
+        table.resize(start + row_size as usize, 0);
+    }
+
+    table
+}
+
+#[test]
+fn test_rtti_padded_rows() {
+    use smxdasm::errors::Error;
+
+    let mut builder = SmxBuilder::new();
+
+    let first = builder.add_name("Direction");
+    let second = builder.add_name("Color");
+
+    // Rows eight bytes wider than the name and three reserved cells.
+    let rows = vec![vec![first, 1, 2, 3, -1, -1], vec![second, 1, 2, 3, -1, -1]];
+
+    let p = SMXFile::new(builder.clone().section("rtti.enums", rtti_table(12, 24, &rows)).build()).unwrap();
+
+    assert_eq!(p.borrow().rtti_enums.as_ref().unwrap().enums(), vec!["Direction", "Color"]);
+
+    let data = builder.clone().section("rtti.enums", rtti_table(16, 24, &rows)).build();
+
+    assert!(matches!(SMXFile::new(data), Err(Error::InvalidSize)));
+
+    // Too narrow for the fields that are read.
+    let data = builder.section("rtti.methods", rtti_table(12, 8, &[vec![first, 0]])).build();
+
+    assert!(matches!(SMXFile::new(data), Err(Error::InvalidSize)));
+}
+
+#[test]
+fn test_main_function() {
+    use smxdasm::errors::Error;
+    use smxdasm::headers::SMXHeader;
+
+    let mut image = fixture(CompressionType::CompressionNone);
+
+    let insns = SMXFile::new(&image).unwrap().borrow().main_function().unwrap();
+
+    // The builder points main at the first PROC.
+    assert_eq!(insns[0].address, 4);
+    assert_eq!(insns[0].info.opcode, V1OPCode::SYSREQ_N);
+
+    // main_offset follows the code size, cell size, version and flags.
+    let main = SMXHeader::new(&image).unwrap().find_section(".code").unwrap().data_offset as usize + 8;
+
+    image[main..main + 4].copy_from_slice(&20i32.to_le_bytes());
+
+    let insns = SMXFile::new(&image).unwrap().borrow().main_function().unwrap();
+
+    assert_eq!(insns[0].info.opcode, V1OPCode::ZERO_PRI);
+
+    image[main..main + 4].copy_from_slice(&(-1i32).to_le_bytes());
+
+    assert!(matches!(SMXFile::new(&image).unwrap().borrow().main_function(), Err(Error::Other(_))));
+}
+
+#[test]
+fn test_data_size_out_of_bounds() {
+    use smxdasm::errors::Error;
+
+    // data_size, memory_size, data_offset, then eight bytes of data.
+    let mut section: Vec<u8> = Vec::new();
+
+    for value in [0x1000u32, 0x1000, 12] {
+        section.extend(&value.to_le_bytes());
+    }
+
+    section.extend(&[0; 8]);
+
+    assert!(matches!(SMXFile::new(SmxBuilder::new().section(".data", section.clone()).build()), Err(Error::SizeOverflow)));
+
+    // Claiming exactly the eight bytes is fine, and reads stop there.
+    section[0..4].copy_from_slice(&8u32.to_le_bytes());
+
+    let p = SMXFile::new(SmxBuilder::new().section(".data", section).build()).unwrap();
+    let f = p.borrow();
+    let data = f.data.as_ref().unwrap();
+
+    assert_eq!(data.read_cell(4).unwrap(), 0);
+    assert!(matches!(data.read_cell(6), Err(Error::OffsetOverflow)));
+    assert!(matches!(data.read_cell(u32::MAX - 1), Err(Error::OffsetOverflow)));
+}
+
+#[test]
+fn test_data_string_at_bounds() {
+    use smxdasm::errors::Error;
+
+    let p = SMXFile::new(SmxBuilder::new().data(b"abc\0defg", 16).build()).unwrap();
+    let f = p.borrow();
+    let data = f.data.as_ref().unwrap();
+
+    assert_eq!(data.string_at(0).unwrap(), "abc");
+
+    // Unterminated at the end of the blob.
+    assert_eq!(data.string_at(4).unwrap(), "defg");
+
+    assert!(matches!(data.string_at(8), Err(Error::InvalidIndex)));
+    assert!(matches!(data.string_at(u32::MAX), Err(Error::InvalidIndex)));
+}
+
+#[test]
+fn test_code_read_cell_bounds() {
+    use smxdasm::errors::Error;
+
+    let data = SmxBuilder::new().code(&[V1OPCode::PROC as i32, V1OPCode::RETN as i32]).build();
+
+    let p = SMXFile::new(&data).unwrap();
+    let f = p.borrow();
+    let code = f.codev1.as_ref().unwrap();
+
+    assert_eq!(code.read_cell(4).unwrap(), V1OPCode::RETN as i32);
+    assert!(matches!(code.read_cell(8), Err(Error::OffsetOverflow)));
+    assert!(matches!(code.read_cell(-4), Err(Error::OffsetOverflow)));
+    assert!(matches!(code.read_cell(i32::MAX - 1), Err(Error::OffsetOverflow)));
+
+    // A code size running past the section is rejected on load.
+    let mut section: Vec<u8> = Vec::new();
+
+    section.extend(&0x1000i32.to_le_bytes());
+    section.push(4);
+    section.push(smxdasm::headers::SMXHeader::CODE_VERSION_CURRENT);
+    section.extend(&0u16.to_le_bytes());
+    section.extend(&0i32.to_le_bytes());
+    section.extend(&20i32.to_le_bytes());
+    section.extend(&0i32.to_le_bytes());
+    section.extend(&(V1OPCode::PROC as i32).to_le_bytes());
+
+    assert!(matches!(SMXFile::new(SmxBuilder::new().section(".code", section).build()), Err(Error::SizeOverflow)));
+}
\ No newline at end of file
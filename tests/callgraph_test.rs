@@ -0,0 +1,228 @@
+extern crate smxdasm;
+
+use smxdasm::builder::SmxBuilder;
+use smxdasm::file::SMXFile;
+use smxdasm::v1opcodes::V1OPCode;
+
+// Four functions, 16 bytes each: a <-> b are mutually recursive, c calls
+// itself and d calls into a.
+fn fixture() -> Vec<u8> {
+    let proc = V1OPCode::PROC as i32;
+    let call = V1OPCode::CALL as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    SmxBuilder::new()
+        .code(&[
+            proc, call, 16, retn,
+            proc, call, 0, retn,
+            proc, call, 32, retn,
+            proc, call, 0, retn,
+        ])
+        .publics(&[("a", 0), ("b", 16), ("c", 32), ("d", 48)])
+        .build()
+}
+
+#[test]
+fn test_call_graph() {
+    let p = SMXFile::new(fixture()).unwrap();
+
+    let graph = p.borrow().call_graph();
+
+    assert_eq!(graph.len(), 4);
+    assert_eq!(graph[&0], vec![16]);
+    assert_eq!(graph[&48], vec![0]);
+}
+
+#[test]
+fn test_call_graph_discovered_functions() {
+    let proc = V1OPCode::PROC as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    // main calls an unnamed leaf that only shows up through the CALL.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, V1OPCode::CALL as i32, 16, retn,
+            proc, V1OPCode::ZERO_PRI as i32, retn,
+            proc, retn,
+        ])
+        .publics(&[("main", 0), ("other", 28)])
+        .build();
+
+    let p = SMXFile::new(data).unwrap();
+    let f = p.borrow();
+
+    let graph = f.call_graph();
+
+    assert_eq!(graph[&0], vec![16]);
+    assert!(graph[&16].is_empty());
+    assert_eq!(f.find_function_name(16).unwrap(), "sub_10");
+}
+
+#[test]
+fn test_recursive_functions() {
+    let p = SMXFile::new(fixture()).unwrap();
+
+    assert_eq!(p.borrow().recursive_functions(), vec![0, 16, 32]);
+}
+
+#[test]
+fn test_leaf_functions() {
+    let proc = V1OPCode::PROC as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    // A pure leaf, a native caller, and two functions calling them.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, V1OPCode::ZERO_PRI as i32, retn,
+            proc, V1OPCode::SYSREQ_N as i32, 0, 0, retn,
+            proc, V1OPCode::CALL as i32, 0, retn,
+            proc, V1OPCode::CALL as i32, 12, retn,
+        ])
+        .natives(&["PrintToServer"])
+        .publics(&[("leaf", 0), ("native", 12), ("a", 32), ("b", 48)])
+        .build();
+
+    let p = SMXFile::new(data).unwrap();
+    let f = p.borrow();
+
+    assert_eq!(f.leaf_functions(false), vec![0, 12]);
+    assert_eq!(f.leaf_functions(true), vec![0]);
+}
+
+#[test]
+fn test_max_call_depth() {
+    use smxdasm::file::CallDepth;
+
+    let proc = V1OPCode::PROC as i32;
+    let call = V1OPCode::CALL as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    // top -> mid -> leaf, and loop -> loop.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, V1OPCode::ZERO_PRI as i32, retn,
+            proc, call, 0, retn,
+            proc, call, 12, call, 0, retn,
+            proc, call, 52, retn,
+        ])
+        .publics(&[("leaf", 0), ("mid", 12), ("top", 28), ("loop", 52)])
+        .build();
+
+    let p = SMXFile::new(data).unwrap();
+    let f = p.borrow();
+
+    assert_eq!(f.max_call_depth("leaf").unwrap(), CallDepth::Bounded(0));
+    assert_eq!(f.max_call_depth("top").unwrap(), CallDepth::Bounded(2));
+    assert_eq!(f.max_call_depth("loop").unwrap(), CallDepth::Recursive);
+
+    // Reaching recursion makes any caller unbounded too.
+    let p = SMXFile::new(fixture()).unwrap();
+
+    assert_eq!(p.borrow().max_call_depth("d").unwrap(), CallDepth::Recursive);
+    assert!(p.borrow().max_call_depth("missing").is_err());
+}
+
+#[test]
+fn test_call_graph_dot() {
+    let proc = V1OPCode::PROC as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, V1OPCode::SYSREQ_N as i32, 0, 1, retn,
+            proc, V1OPCode::CALL as i32, 0, retn,
+        ])
+        .natives(&["PrintToServer"])
+        .publics(&[("Print\"Quoted\"", 0), ("OnPluginStart", 20)])
+        .build();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let dot = p.borrow().call_graph_dot();
+
+    assert!(dot.starts_with("digraph calls {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("    f_0 [label=\"Print\\\"Quoted\\\"\"];\n"));
+    assert!(dot.contains("    f_14 [label=\"OnPluginStart\"];\n"));
+    assert!(dot.contains("    n_0 [label=\"PrintToServer\", shape=box];\n"));
+    assert!(dot.contains("    f_14 -> f_0;\n"));
+    assert!(dot.contains("    f_0 -> n_0 [style=dashed];\n"));
+}
+
+#[test]
+fn test_functions() {
+    use smxdasm::file::FunctionInfo;
+
+    let proc = V1OPCode::PROC as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    // Two publics sharing an address, and a callee only reachable via CALL.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, V1OPCode::CALL as i32, 16, retn,
+            proc, retn,
+        ])
+        .publics(&[("main", 0), ("alias", 0)])
+        .build();
+
+    let p = SMXFile::new(data).unwrap();
+
+    assert_eq!(p.borrow().functions(), vec![
+        FunctionInfo { address: 0, name: "main".to_string(), is_public: true },
+        FunctionInfo { address: 16, name: "sub_10".to_string(), is_public: false },
+    ]);
+
+    // Every public of the sample plugin shows up exactly once.
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+    let f = p.borrow();
+
+    let functions = f.functions();
+
+    for pubfun in f.publics.as_ref().unwrap().entries_ref() {
+        assert_eq!(functions.iter().filter(|fun| fun.address == pubfun.address).count(), 1);
+    }
+}
+
+#[test]
+fn test_unreachable_functions() {
+    let proc = V1OPCode::PROC as i32;
+    let call = V1OPCode::CALL as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    let mut builder = SmxBuilder::new();
+
+    // Only rtti.methods knows about the orphaned c <-> d pair.
+    let methods = [("start", 0), ("b", 16), ("c", 32), ("d", 48)];
+
+    let mut table: Vec<u8> = Vec::new();
+
+    for cell in &[12, 16, methods.len() as i32] {
+        table.extend(&cell.to_le_bytes());
+    }
+
+    for (name, start) in &methods {
+        for cell in &[builder.add_name(name), *start, start + 16, 0] {
+            table.extend(&cell.to_le_bytes());
+        }
+    }
+
+    let data = builder
+        .code(&[
+            proc, call, 16, retn,
+            proc, call, 16, retn,
+            proc, call, 48, retn,
+            proc, call, 32, retn,
+        ])
+        .publics(&[("start", 0)])
+        .section("rtti.methods", table)
+        .build();
+
+    let p = SMXFile::new(data).unwrap();
+
+    assert_eq!(p.borrow().unreachable_functions(), vec![32, 48]);
+
+    // Every function of the sample plugin is public.
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    assert!(p.borrow().unreachable_functions().is_empty());
+}
\ No newline at end of file
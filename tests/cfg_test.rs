@@ -0,0 +1,70 @@
+extern crate smxdasm;
+
+use smxdasm::v1cfg::basic_blocks;
+use smxdasm::v1disassembler::V1Disassembler;
+use smxdasm::v1opcodes::V1OPCode;
+
+fn assemble(cells: &[i32]) -> Vec<u8> {
+    cells.iter().flat_map(|c| c.to_le_bytes().to_vec()).collect()
+}
+
+#[test]
+fn test_basic_blocks_if_else() {
+    // if (arg) { pri = 1 } else { pri = 2 } return
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::LOAD_S_PRI as i32, 12,
+        V1OPCode::JZER as i32, 36,
+        V1OPCode::CONST_PRI as i32, 1,
+        V1OPCode::JUMP as i32, 44,
+        V1OPCode::CONST_PRI as i32, 2,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let blocks = basic_blocks(&insns);
+
+    assert_eq!(blocks.len(), 4);
+
+    assert_eq!(blocks[0].start, 4);
+    assert_eq!(blocks[0].insns, 0..2);
+    assert_eq!(blocks[0].successors, vec![20, 36]);
+
+    assert_eq!(blocks[1].start, 20);
+    assert_eq!(blocks[1].successors, vec![44]);
+
+    assert_eq!(blocks[2].start, 36);
+    assert_eq!(blocks[2].successors, vec![44]);
+
+    assert_eq!(blocks[3].start, 44);
+    assert!(blocks[3].successors.is_empty());
+}
+
+#[test]
+fn test_basic_blocks_switch() {
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::LOAD_S_PRI as i32, 12,
+        V1OPCode::SWITCH as i32, 20,
+        V1OPCode::CASETBL as i32, 2, 56, 0, 48, 1, 52,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let blocks = basic_blocks(&insns);
+
+    let starts: Vec<i32> = blocks.iter().map(|block| block.start).collect();
+
+    assert_eq!(starts, vec![4, 20, 48, 52, 56]);
+
+    // SWITCH goes through its table, which reaches every case and the default.
+    assert_eq!(blocks[0].successors, vec![20]);
+    assert_eq!(blocks[1].successors, vec![48, 52, 56]);
+
+    // Case bodies fall through into each other.
+    assert_eq!(blocks[2].successors, vec![52]);
+}
\ No newline at end of file
@@ -0,0 +1,241 @@
+extern crate smxdasm;
+
+use smxdasm::v1disassembler::V1Disassembler;
+use smxdasm::v1opcodes::V1OPCode;
+use smxdasm::errors::Error;
+
+fn assemble(cells: &[i32]) -> Vec<u8> {
+    cells.iter().flat_map(|c| c.to_le_bytes().to_vec()).collect()
+}
+
+#[test]
+fn test_disassemble_bytes() {
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::PUSH2_C as i32, 1, 2,
+        V1OPCode::CALL as i32, 0,
+        V1OPCode::STACK as i32, 8,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    assert_eq!(insns.len(), 4);
+
+    assert_eq!(insns[0].address, 4);
+    assert_eq!(insns[0].info.opcode, V1OPCode::PUSH2_C);
+    assert_eq!(insns[0].params, vec![1, 2]);
+
+    assert_eq!(insns[1].info.opcode, V1OPCode::CALL);
+    assert_eq!(insns[1].params, vec![0]);
+
+    assert_eq!(insns[3].info.opcode, V1OPCode::RETN);
+}
+
+#[test]
+fn test_disassemble_bytes_requires_proc() {
+    let code = assemble(&[V1OPCode::RETN as i32]);
+
+    assert!(V1Disassembler::disassemble_bytes(&code, 0).is_err());
+}
+
+#[test]
+fn test_resolve_switch() {
+    use smxdasm::v1disassembler::{resolve_switch, SwitchTable};
+
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::LOAD_S_PRI as i32, 12,
+        V1OPCode::SWITCH as i32, 20,
+        V1OPCode::CASETBL as i32, 2, 56, 0, 48, 1, 52,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    assert_eq!(insns[1].info.opcode, V1OPCode::SWITCH);
+
+    let table = resolve_switch(&insns, &insns[1]).unwrap();
+
+    assert_eq!(table, SwitchTable {
+        default: 56,
+        cases: vec![(0, 48), (1, 52)],
+    });
+
+    // Only SWITCH instructions resolve.
+    assert!(resolve_switch(&insns, &insns[0]).is_none());
+
+    let listing = smxdasm::v1listing::function_listing(&smxdasm::file::SMXFile::default(), &insns, &Default::default());
+
+    assert!(listing.contains("switch 0x14\n"));
+    assert!(listing.contains("case 0x1: 0x34\n"));
+    assert!(listing.contains("default: 0x38\n"));
+}
+
+#[test]
+fn test_instruction_display() {
+    use smxdasm::v1disassembler::{V1Instruction, V1OPCodeInfo, V1Param};
+
+    let load = V1Instruction {
+        address: 0,
+        info: V1OPCodeInfo {
+            opcode: V1OPCode::LOAD_S_PRI,
+            name: "load.s.pri".into(),
+            params: vec![V1Param::Stack],
+        },
+        params: vec![-12],
+    };
+
+    assert_eq!(load.to_string(), "load.s.pri -12");
+
+    let push = V1Instruction {
+        address: 4,
+        info: V1OPCodeInfo {
+            opcode: V1OPCode::PUSH2_C,
+            name: "push2.c".into(),
+            params: vec![V1Param::Constant, V1Param::Constant],
+        },
+        params: vec![255, -1],
+    };
+
+    assert_eq!(push.to_string(), "push2.c 0xff -0x1");
+
+    // Instructions from the disassembler render the same way.
+    let code = assemble(&[V1OPCode::PROC as i32, V1OPCode::STOR_S_PRI as i32, 12, V1OPCode::JUMP as i32, 0]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let text: Vec<String> = insns.iter().map(|insn| insn.to_string()).collect();
+
+    assert_eq!(text, vec!["stor.s.pri +12", "jump 0"]);
+}
+
+#[test]
+fn test_disassemble_unknown_opcode() {
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::PUSH_C as i32, 1,
+        0xfe,
+        V1OPCode::RETN as i32,
+    ]);
+
+    match V1Disassembler::disassemble_bytes(&code, 0) {
+        Err(Error::UnknownOpcode(0xfe)) => {},
+        other => panic!("expected UnknownOpcode, got {:?}", other.map(|insns| insns.len())),
+    }
+}
+
+#[test]
+fn test_switch_table() {
+    use smxdasm::v1disassembler::SwitchTable;
+
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::LOAD_S_PRI as i32, 12,
+        V1OPCode::SWITCH as i32, 20,
+        V1OPCode::CASETBL as i32, 3, 68, 0, 56, 5, 60, -1, 64,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::ZERO_PRI as i32,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    assert_eq!(insns[2].switch_table(), Some(SwitchTable {
+        default: 68,
+        cases: vec![(0, 56), (5, 60), (-1, 64)],
+    }));
+
+    // The raw layout is still available.
+    assert_eq!(insns[2].params, vec![3, 68, 0, 56, 5, 60, -1, 64]);
+
+    assert!(insns[1].switch_table().is_none());
+}
+
+#[test]
+fn test_opcode_info() {
+    use smxdasm::v1disassembler::{opcode_info, all_opcodes};
+
+    let push2 = opcode_info(V1OPCode::PUSH2_C).unwrap();
+
+    assert_eq!(push2.name, "push2.c");
+    assert_eq!(push2.params.len(), 2);
+
+    assert!(opcode_info(V1OPCode::NONE).is_none());
+
+    let all = all_opcodes();
+
+    assert!(all.iter().any(|info| info.opcode == V1OPCode::PUSH2_C));
+    assert!(all.windows(2).all(|pair| (pair[0].opcode.clone() as u32) < (pair[1].opcode.clone() as u32)));
+}
+
+#[test]
+fn test_opcode_by_name() {
+    use smxdasm::v1disassembler::{opcode_info, opcode_by_name};
+
+    let name = &opcode_info(V1OPCode::LOAD_S_PRI).unwrap().name;
+
+    assert_eq!(name, "load.s.pri");
+    assert!(opcode_by_name(name).unwrap().opcode == V1OPCode::LOAD_S_PRI);
+
+    assert!(opcode_by_name("LOAD_S_PRI").unwrap().opcode == V1OPCode::LOAD_S_PRI);
+    assert!(opcode_by_name("Push2.C").unwrap().opcode == V1OPCode::PUSH2_C);
+    assert!(opcode_by_name("load.s").is_none());
+}
+
+#[test]
+fn test_disassemble_truncated() {
+    use smxdasm::errors::Error;
+
+    // PUSH5.C with only two of its five operands present.
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::PUSH5_C as i32, 1, 2,
+    ]);
+
+    assert!(matches!(V1Disassembler::disassemble_bytes(&code, 0), Err(Error::OffsetOverflow)));
+
+    // Starting outside the code entirely.
+    assert!(matches!(V1Disassembler::disassemble_bytes(&code, 16), Err(Error::OffsetOverflow)));
+    assert!(matches!(V1Disassembler::disassemble_bytes(&code, -4), Err(Error::OffsetOverflow)));
+}
+
+#[test]
+fn test_disassemble_bogus_casetbl() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::SMXFile;
+
+    for ncases in [-1, 2, i32::MAX] {
+        let cells = [V1OPCode::PROC as i32, V1OPCode::CASETBL as i32, ncases, 0, V1OPCode::RETN as i32];
+
+        assert!(matches!(V1Disassembler::disassemble_bytes(&assemble(&cells), 0), Err(Error::OffsetOverflow)), "{} cases", ncases);
+
+        let data = SmxBuilder::new().code(&cells).publics(&[("OnPluginStart", 0)]).build();
+
+        assert!(matches!(SMXFile::new(data), Err(Error::OffsetOverflow)), "{} cases", ncases);
+    }
+}
+
+#[test]
+fn test_switch_table_bogus_count() {
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::CASETBL as i32, 1, 16, 7, 16,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let mut insn = V1Disassembler::disassemble_bytes(&code, 0).unwrap().remove(0);
+
+    // A count that disagrees with the operands only reports what is there.
+    insn.params[0] = i32::MAX;
+
+    assert_eq!(insn.switch_table().unwrap().cases, vec![(7, 16)]);
+    assert_eq!(insn.references(), vec![16, 16]);
+
+    insn.params[0] = -1;
+
+    assert!(insn.switch_table().unwrap().cases.is_empty());
+}
\ No newline at end of file
@@ -5,7 +5,7 @@ extern crate smxdasm;
 
 #[test]
 fn test_file() {
-    let mut file = File::open("F:\\Github\\smx-dasm-rs\\tests\\Source-Chat-Relay.smx").unwrap();
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
 
     let mut data = Vec::new();
 
@@ -36,18 +36,18 @@ fn test_file() {
     println!("========== HEADER ==========");
 
     if let Some(opt) = &f.names {
-        let names = opt.borrow();
+        let mut names = opt.borrow_mut();
         println!("========== Name Table Names ==========");
-        for (_, name) in &names.names() {
+        for (_, name) in names.iter() {
             println!("{}", name);
         }
         println!("========== Name Table Names ==========");
     }
 
     if let Some(opt) = &f.debug_names {
-        let names = opt.borrow();
+        let mut names = opt.borrow_mut();
         println!("========== Debug Name Table Names ==========");
-        for (_, name) in &names.names() {
+        for (_, name) in names.iter() {
             println!("{}", name);
         }
         println!("========== Debug Name Table Names ==========");
@@ -316,4 +316,921 @@ fn test_file() {
         }
         println!("========== Debug Locals ==========");
     }
+}
+
+#[test]
+fn test_verify_publics() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let results = f.verify_publics();
+
+    assert_eq!(results.len(), f.publics.as_ref().unwrap().size());
+
+    for (entry, valid) in &results {
+        assert!(valid, "{} does not start with PROC", entry.name);
+    }
+}
+
+#[test]
+fn test_xrefs_to() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let mut checked = 0;
+
+    for insns in f.functions.values() {
+        for insn in insns.iter().filter(|i| i.info.opcode == smxdasm::v1opcodes::V1OPCode::CALL) {
+            assert!(f.xrefs_to(insn.params[0]).contains(&insn.address));
+
+            checked += 1;
+        }
+    }
+
+    assert!(checked > 0);
+    assert!(f.xrefs_to(-1).is_empty());
+}
+
+#[test]
+fn test_build_timestamp() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    // The sample plugin does not embed a build timestamp.
+    assert_eq!(p.borrow().build_timestamp(), None);
+}
+
+#[test]
+fn test_optional_natives() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let optional = p.borrow().optional_natives();
+
+    assert!(optional.contains(&"GetFeatureStatus".to_string()));
+    assert!(optional.contains(&"RequireFeature".to_string()));
+    assert!(!optional.contains(&"PrintToServer".to_string()));
+}
+
+#[test]
+fn test_unused_natives() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let referenced: Vec<String> = f.referenced_natives().into_iter().map(|n| n.name).collect();
+    let unused: Vec<String> = f.unused_natives().into_iter().map(|n| n.name).collect();
+
+    assert_eq!(referenced.len() + unused.len(), f.natives.as_ref().unwrap().size());
+    assert!(referenced.contains(&"PrintToServer".to_string()));
+    assert!(!unused.contains(&"PrintToServer".to_string()));
+
+    for name in &unused {
+        assert!(!referenced.contains(name));
+    }
+}
+
+#[test]
+fn test_instruction_at() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    for insns in f.functions.values() {
+        for insn in insns {
+            let found = f.instruction_at(insn.address).unwrap().unwrap();
+
+            assert_eq!(found.address, insn.address);
+            assert_eq!(found.info.opcode, insn.info.opcode);
+            assert_eq!(found.params, insn.params);
+        }
+    }
+
+    let first = &f.functions.values().next().unwrap()[0];
+
+    // Operand cells are not instruction boundaries.
+    if !first.params.is_empty() {
+        assert!(f.instruction_at(first.address + 4).unwrap().is_none());
+    }
+
+    assert!(f.instruction_at(-4).is_err());
+    assert!(f.instruction_at(f.codev1.as_ref().unwrap().header().code_size).is_err());
+}
+
+#[test]
+fn test_memory_layout() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let section = f.data.as_ref().unwrap();
+    let header = section.header();
+    let layout = section.memory_layout();
+
+    assert_eq!(layout.data_start, 0);
+    assert_eq!(layout.data_end, header.data_size);
+    assert_eq!(layout.heap_start, header.data_size);
+    assert_eq!(layout.stack_top, header.memory_size);
+    assert!(layout.heap_start <= layout.stack_top);
+}
+
+#[test]
+fn test_pubvar_values() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let values = f.pubvar_values().unwrap();
+
+    assert_eq!(values.len(), f.pubvars.as_ref().unwrap().size());
+
+    // Uninitialized until the plugin is loaded.
+    assert!(values.contains(&("MaxClients".to_string(), 0)));
+
+    // First field of the plugin's PlVers struct.
+    assert!(values.contains(&("__version".to_string(), 5)));
+}
+
+#[test]
+fn test_legacy_enum_members() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::sections::Tag;
+    use smxdasm::v1types::TagEntry;
+
+    let mut builder = SmxBuilder::new();
+
+    // (address, tag, ident, name): two globals and a local carry the Color
+    // tag (5); the function returning it and the untagged global don't count.
+    let symbols: &[(i32, i16, u8, &str)] = &[
+        (0, 5, 1, "g_Background"),
+        (4, 0, 1, "g_Count"),
+        (8, 5, 1, "g_Foreground"),
+        (0, 5, 9, "GetColor"),
+        (-4, 5, 1, "color"),
+    ];
+
+    let mut section: Vec<u8> = Vec::new();
+
+    for (address, tag, ident, name) in symbols {
+        section.extend(&address.to_le_bytes());
+        section.extend(&tag.to_le_bytes());
+        section.extend(&0u32.to_le_bytes());
+        section.extend(&0u32.to_le_bytes());
+        section.push(*ident);
+        section.push(0);
+        section.extend(&0u16.to_le_bytes());
+        section.extend(&builder.add_name(name).to_le_bytes());
+    }
+
+    let p = smxdasm::file::SMXFile::new(builder.section(".dbg.symbols", section).build()).unwrap();
+
+    let f = p.borrow();
+
+    let tag = |id: u32, flags: u32| Tag::new(TagEntry {
+        tag: id | flags,
+        name_offset: 0,
+        name: "Color".to_string(),
+    });
+
+    assert_eq!(f.legacy_enum_members(&tag(5, TagEntry::ENUM)), vec!["g_Background", "g_Foreground", "color"]);
+
+    // Only enum tags are correlated.
+    assert!(f.legacy_enum_members(&tag(5, 0)).is_empty());
+    assert!(f.legacy_enum_members(&tag(6, TagEntry::ENUM)).is_empty());
+}
+
+#[test]
+fn test_strict_parsing() {
+    use smxdasm::file::{SMXFile, ParseOptions};
+    use smxdasm::errors::Error;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let strict = ParseOptions { strict: true };
+
+    assert!(SMXFile::new_with_options(&data, strict.clone()).is_ok());
+
+    // A renamed section is skipped leniently but rejected in strict mode.
+    let mut renamed = data.clone();
+    let pos = renamed.windows(9).position(|w| w == b".dbg.info").unwrap();
+    renamed[pos + 8] = b'x';
+
+    assert!(SMXFile::new(&renamed).unwrap().borrow().debug_info.is_none());
+
+    match SMXFile::new_with_options(&renamed, strict.clone()) {
+        Err(Error::UnknownSection(name)) => assert_eq!(name, ".dbg.infx"),
+        _ => panic!("expected an unknown section error"),
+    }
+
+    // So is a version newer than the parser supports.
+    let mut versioned = data.clone();
+    versioned[4] = 0x08;
+
+    assert!(SMXFile::new(&versioned).is_ok());
+    assert!(matches!(SMXFile::new_with_options(&versioned, strict), Err(Error::UnsupportedVersion(0x0108))));
+}
+
+#[test]
+fn test_strict_cell_alignment() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::{SMXFile, ParseOptions};
+    use smxdasm::errors::Error;
+
+    let strict = ParseOptions { strict: true };
+
+    let aligned = SmxBuilder::new().data(&[1, 2, 3, 4], 8).build();
+
+    assert!(SMXFile::new_with_options(&aligned, strict.clone()).is_ok());
+
+    // Three bytes of data leave .data short of a whole cell.
+    let misaligned = SmxBuilder::new().data(&[1, 2, 3], 8).build();
+
+    assert!(SMXFile::new(&misaligned).is_ok());
+    assert!(matches!(SMXFile::new_with_options(&misaligned, strict), Err(Error::InvalidSize)));
+}
+
+#[test]
+fn test_function_signature_bytes() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let sig = f.function_signature_bytes("OnPluginStart").unwrap();
+
+    // PROC followed by the function body up to its final RETN.
+    assert_eq!(&sig[..4], &[Some(46), Some(0), Some(0), Some(0)]);
+    assert_eq!(&sig[sig.len() - 4..], &[Some(48), Some(0), Some(0), Some(0)]);
+    assert_eq!(sig.len() % 4, 0);
+
+    let insns = f.functions.get(&18764).unwrap();
+
+    // One opcode cell per instruction plus the PROC, everything else masked.
+    assert_eq!(sig.iter().filter(|b| b.is_some()).count(), 4 * (insns.len() + 1));
+
+    assert!(f.function_signature_bytes("NoSuchFunction").is_err());
+}
+
+#[test]
+fn test_is_known_section() {
+    use smxdasm::file::{SMXFile, is_known_section};
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    // Every section handled by the loader.
+    let handled = [
+        ".names", ".dbg.strings", ".dbg.info", ".natives", ".publics", ".pubvars",
+        ".tags", ".data", ".code", ".dbg.files", ".dbg.lines", ".dbg.natives",
+        ".dbg.symbols", ".dbg.methods", ".dbg.globals", ".dbg.locals", "rtti.data", "rtti.classdefs",
+        "rtti.enumstructs", "rtti.enumstruct_fields", "rtti.fields", "rtti.methods",
+        "rtti.natives", "rtti.enums", "rtti.typedefs", "rtti.typesets",
+    ];
+
+    for name in handled.iter() {
+        assert!(is_known_section(name), "{} should be known", name);
+    }
+
+    let mut renamed = data.clone();
+    let pos = renamed.windows(9).position(|w| w == b".dbg.info").unwrap();
+    renamed[pos + 8] = b'x';
+
+    let p = SMXFile::new(&renamed).unwrap();
+    let f = p.borrow();
+
+    for section in &f.header.sections {
+        let unknown = f.unknown_sections.iter().any(|s| s.name == section.name);
+
+        assert_eq!(is_known_section(&section.name), !unknown);
+    }
+}
+
+#[test]
+fn test_data_accesses() {
+    use smxdasm::file::AccessKind;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let accesses = f.data_accesses();
+
+    // g_cHost is assigned in OnPluginStart and read elsewhere.
+    let host: Vec<&(i32, i32, AccessKind)> = accesses.iter().filter(|a| a.1 == 0x96f8).collect();
+
+    assert!(host.iter().any(|a| a.2 == AccessKind::Write && a.0 > 18764 && a.0 < 19896));
+    assert!(host.iter().any(|a| a.2 == AccessKind::Read));
+
+    let data_size = f.data.as_ref().unwrap().header().data_size as i32;
+
+    assert!(accesses.iter().all(|a| a.1 >= 0 && a.1 < data_size));
+    assert!(accesses.windows(2).all(|w| w[0].0 <= w[1].0));
+}
+
+#[test]
+fn test_methodmap_bodies() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let bodies = f.methodmap_bodies("ByteBuffer").unwrap();
+
+    let names: Vec<&str> = bodies.iter().map(|(name, _)| name.as_str()).collect();
+
+    assert_eq!(names, vec!["Cursor.get", "Cursor.set", "WriteByte", "WriteString", "ReadByte", "ReadString", "Dump", "Close"]);
+
+    // Cursor.get spans [13776, 13848).
+    let (_, insns) = &bodies[0];
+
+    assert_eq!(insns.first().unwrap().address, 13780);
+    assert!(insns.iter().all(|insn| insn.address < 13848));
+    assert_eq!(insns.last().unwrap().info.opcode, smxdasm::v1opcodes::V1OPCode::RETN);
+
+    assert!(f.methodmap_bodies("NoSuchClass").unwrap().is_empty());
+}
+
+#[test]
+fn test_function_summary() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let summary = f.function_summary("OnPluginStart").unwrap();
+
+    assert_eq!(summary.address, 18764);
+    assert_eq!(summary.size, 19896 - 18764);
+    assert_eq!(summary.instruction_count, f.functions[&18764].len());
+    assert_eq!(summary.signature.as_deref(), Some("function void ()"));
+    assert_eq!(summary.source.as_ref().map(|(_, line)| *line), Some(331));
+    assert!(summary.source.unwrap().0.ends_with("Source-Chat-Relay.sp"));
+
+    // OnPluginStart declares no locals, so .dbg.methods skips it.
+    assert!(!summary.has_debug_info);
+    assert!(f.function_summary("OnConfigsExecuted").unwrap().has_debug_info);
+
+    assert!(f.function_summary("NoSuchFunction").is_err());
+}
+
+#[test]
+fn test_sections_by_offset() {
+    use smxdasm::builder::SmxBuilder;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let sections = f.sections_by_offset();
+
+    assert_eq!(sections.len(), f.header.sections.len());
+    assert!(sections.windows(2).all(|w| w[0].data_offset <= w[1].data_offset));
+    assert_eq!(sections[0].data_offset, f.header.data_offset);
+
+    assert!(f.gaps_between_sections().is_empty());
+
+    // Shrink the first section so its trailing bytes belong to nothing.
+    let mut image = SmxBuilder::new().section(".test", vec![0; 8]).section(".other", vec![0; 4]).build();
+
+    image[32..36].copy_from_slice(&4i32.to_le_bytes());
+
+    let p = smxdasm::file::SMXFile::new(image).unwrap();
+
+    let f = p.borrow();
+
+    let start = f.header.data_offset;
+
+    assert_eq!(f.gaps_between_sections(), vec![(start + 4, start + 8)]);
+}
+
+#[test]
+fn test_registered_convars() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let convars = p.borrow().registered_convars();
+
+    assert_eq!(&convars[..3], &["rf_scr_version", "rf_scr_host", "rf_scr_port"]);
+    assert!(convars.contains(&"rf_scr_flag".to_string()));
+
+    // The relay registers no commands.
+    assert!(p.borrow().registered_commands().is_empty());
+}
+
+#[test]
+fn test_registered_commands() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::v1opcodes::V1OPCode;
+
+    let proc = V1OPCode::PROC as i32;
+    let retn = V1OPCode::RETN as i32;
+    let push2_c = V1OPCode::PUSH2_C as i32;
+    let sysreq_n = V1OPCode::SYSREQ_N as i32;
+
+    // RegConsoleCmd("sm_hello", ...) and RegAdminCmd("sm_kick", ...), the
+    // second reached through PUSH_PRI after CONST_PRI.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, push2_c, 0, 0, sysreq_n, 0, 2, retn,
+            proc, V1OPCode::PUSH_C as i32, 0, V1OPCode::CONST_PRI as i32, 12, V1OPCode::PUSH_PRI as i32, sysreq_n, 1, 2, retn,
+        ])
+        .data(b"sm_hello\0\0\0\0sm_kick\0", 32)
+        .natives(&["RegConsoleCmd", "RegAdminCmd"])
+        .publics(&[("OnPluginStart", 0), ("Register", 32)])
+        .build();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    assert_eq!(p.borrow().registered_commands(), vec!["sm_hello", "sm_kick"]);
+    assert!(p.borrow().registered_convars().is_empty());
+}
+
+#[test]
+fn test_native_calls_bogus_argc() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::v1opcodes::V1OPCode;
+
+    let proc = V1OPCode::PROC as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    // Negative and absurd argument counts, through both SYSREQ forms.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, V1OPCode::SYSREQ_N as i32, 0, -1, retn,
+            proc, V1OPCode::PUSH_C as i32, -4, V1OPCode::SYSREQ_C as i32, 0, retn,
+            proc, V1OPCode::SYSREQ_N as i32, 0, i32::MAX, retn,
+        ])
+        .data(b"sm_hello\0", 16)
+        .natives(&["RegConsoleCmd"])
+        .publics(&[("a", 0), ("b", 20), ("c", 44)])
+        .build();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    assert!(p.borrow().registered_commands().is_empty());
+    assert!(p.borrow().registered_convars().is_empty());
+    assert!(p.borrow().optional_natives().is_empty());
+}
+
+#[test]
+fn test_debug_natives() {
+    use smxdasm::builder::SmxBuilder;
+
+    let mut builder = SmxBuilder::new();
+
+    let mut section: Vec<u8> = Vec::new();
+
+    section.extend(&2u32.to_le_bytes());
+
+    // (ident, tag, name, dims) and (index, name, return tag, args).
+    type Arg<'a> = (u8, i16, &'a str, &'a [u32]);
+
+    let natives: &[(i32, &str, i16, &[Arg])] = &[
+        (0, "PrintToServer", 0, &[(3, 0, "format", &[0]), (11, 0, "...", &[])]),
+        (1, "GetClientName", 1, &[(1, 0, "client", &[]), (3, 0, "name", &[0]), (1, 0, "maxlen", &[])]),
+    ];
+
+    for (index, name, tag, args) in natives {
+        let name = builder.add_name(name);
+
+        section.extend(&index.to_le_bytes());
+        section.extend(&name.to_le_bytes());
+        section.extend(&tag.to_le_bytes());
+        section.extend(&(args.len() as u16).to_le_bytes());
+
+        for (ident, tag, name, dims) in args.iter() {
+            let name = builder.add_name(name);
+
+            section.push(*ident);
+            section.extend(&tag.to_le_bytes());
+            section.extend(&(dims.len() as u16).to_le_bytes());
+            section.extend(&name.to_le_bytes());
+
+            for size in dims.iter() {
+                section.extend(&0i16.to_le_bytes());
+                section.extend(&size.to_le_bytes());
+            }
+        }
+    }
+
+    let p = smxdasm::file::SMXFile::new(builder.section(".dbg.natives", section).build()).unwrap();
+
+    let f = p.borrow();
+
+    let debug_natives = f.debug_natives.as_ref().unwrap();
+
+    for native in debug_natives.entries() {
+        let args: Vec<String> = native.args.iter().map(|arg| format!("{}{}", arg.name, "[]".repeat(arg.dims.len()))).collect();
+
+        println!("{} #{}({})", native.name, native.index, args.join(", "));
+    }
+
+    assert_eq!(debug_natives.len(), 2);
+
+    let entries = debug_natives.entries();
+
+    assert_eq!(entries[0].name, "PrintToServer");
+    assert_eq!(entries[0].args[1].ident, 11);
+    assert_eq!(entries[1].index, 1);
+    assert_eq!(entries[1].tag_id, 1);
+
+    let args: Vec<&str> = entries[1].args.iter().map(|arg| arg.name.as_str()).collect();
+
+    assert_eq!(args, vec!["client", "name", "maxlen"]);
+    assert_eq!(entries[1].args[1].dims.len(), 1);
+}
+
+#[test]
+fn test_legacy_debug_symbols() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::headers::SMXHeader;
+
+    // (address, code_start, code_end, ident, scope, name, dims)
+    type Symbol<'a> = (i32, u32, u32, u8, u8, &'a str, &'a [u32]);
+
+    let symbols: &[Symbol] = &[
+        (0, 0, 0, 1, 0, "g_Count", &[]),
+        (4, 0, 0, 3, 0, "g_Buffer", &[8]),
+        (0, 0, 32, 9, 0, "OnPluginStart", &[]),
+        (-4, 8, 32, 1, 1, "i", &[]),
+        (12, 0, 32, 1, 1, "client", &[]),
+    ];
+
+    // SP 1.0 plugins without .dbg.natives pad the 16-bit tags; later ones
+    // are packed.
+    let legacy = |version: u16, padded: bool, with_info: bool| -> Vec<u8> {
+        let mut builder = SmxBuilder::new().version(version);
+
+        let mut section: Vec<u8> = Vec::new();
+
+        for (address, code_start, code_end, ident, scope, name, dims) in symbols {
+            let name = builder.add_name(name);
+
+            section.extend(&address.to_le_bytes());
+            section.extend(&0i16.to_le_bytes());
+
+            if padded {
+                section.extend(&[0, 0]);
+            }
+
+            section.extend(&code_start.to_le_bytes());
+            section.extend(&code_end.to_le_bytes());
+            section.push(*ident);
+            section.push(*scope);
+            section.extend(&(dims.len() as u16).to_le_bytes());
+            section.extend(&name.to_le_bytes());
+
+            for size in dims.iter() {
+                section.extend(&0i16.to_le_bytes());
+
+                if padded {
+                    section.extend(&[0, 0]);
+                }
+
+                section.extend(&size.to_le_bytes());
+            }
+        }
+
+        if with_info {
+            let mut info: Vec<u8> = Vec::new();
+
+            for count in &[0i32, 0, symbols.len() as i32, 1] {
+                info.extend(&count.to_le_bytes());
+            }
+
+            builder = builder.section(".dbg.info", info);
+        }
+
+        builder.section(".dbg.symbols", section).build()
+    };
+
+    for (version, padded, with_info) in &[(SMXHeader::SP1_VERSION_1_0, true, true), (SMXHeader::SP1_VERSION_1_1, false, false)] {
+        let p = smxdasm::file::SMXFile::new(legacy(*version, *padded, *with_info)).unwrap();
+
+        let f = p.borrow();
+
+        assert_eq!(f.header.debug_packed, *padded);
+        assert_eq!(f.debug_symbols.as_ref().unwrap().len(), symbols.len());
+
+        assert_eq!(f.find_global_name(0).as_deref(), Some("g_Count"));
+        assert_eq!(f.find_global_name(4 + 7 * 4).as_deref(), Some("g_Buffer"));
+        assert_eq!(f.find_global_name(4 + 8 * 4), None);
+
+        assert_eq!(f.find_local_name(16, -4).as_deref(), Some("i"));
+        assert_eq!(f.find_local_name(16, 12).as_deref(), Some("client"));
+        assert_eq!(f.find_local_name(4, -4), None);
+
+        assert!(f.is_function_at_address(0));
+        assert!(!f.is_function_at_address(4));
+    }
+}
+
+#[test]
+fn test_legacy_debug_symbol_callee() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::v1opcodes::V1OPCode;
+
+    let mut builder = SmxBuilder::new();
+
+    // A packed function record for the non-public callee at 0x10.
+    let mut section: Vec<u8> = Vec::new();
+
+    section.extend(&16i32.to_le_bytes());
+    section.extend(&0i16.to_le_bytes());
+    section.extend(&16u32.to_le_bytes());
+    section.extend(&28u32.to_le_bytes());
+    section.push(9);
+    section.push(0);
+    section.extend(&0u16.to_le_bytes());
+    section.extend(&builder.add_name("Helper").to_le_bytes());
+
+    let data = builder
+        .code(&[
+            V1OPCode::PROC as i32, V1OPCode::CALL as i32, 16, V1OPCode::RETN as i32,
+            V1OPCode::PROC as i32, V1OPCode::ZERO_PRI as i32, V1OPCode::RETN as i32,
+        ])
+        .publics(&[("OnPluginStart", 0)])
+        .section(".dbg.symbols", section)
+        .build();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+    let f = p.borrow();
+
+    let mut addrs: Vec<i32> = f.functions.keys().copied().collect();
+    addrs.sort();
+
+    assert_eq!(addrs, vec![0, 16]);
+    assert_eq!(f.find_function_name(16).as_deref(), Some("Helper"));
+    assert!(f.is_function_at_address(16));
+}
+
+#[test]
+fn test_instructions_at() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let insns = f.instructions_at(18764).unwrap();
+
+    assert_eq!(insns[0].address, 18768);
+    assert_eq!(insns.len(), f.functions[&18764].len());
+
+    // Only PROC addresses are keyed.
+    assert!(f.instructions_at(18768).is_none());
+}
+
+#[test]
+fn test_disassemble_function_by_name() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let insns = f.disassemble_function_by_name("OnPluginStart").unwrap();
+
+    assert_eq!(insns.len(), f.functions[&18764].len());
+    assert_eq!(insns[0].address, 18768);
+
+    assert!(matches!(f.disassemble_function_by_name("NoSuchFunction"), Err(smxdasm::errors::Error::InvalidIndex)));
+}
+
+#[test]
+fn test_source_location() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    // Inside OnPluginStart.
+    let (path, line) = f.source_location(18800).unwrap();
+
+    assert!(path.ends_with("Source-Chat-Relay.sp"));
+    assert_eq!(line, 336);
+
+    assert_eq!(f.source_location(18764).unwrap().1, 331);
+}
+
+#[test]
+fn test_debug_lines_find_line() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let lines = f.debug_lines.as_ref().unwrap();
+
+    // OnPluginStart opens on line 331.
+    assert_eq!(lines.find_line(18764), Some(331));
+    assert_eq!(lines.find_line(18800), Some(336));
+    assert_eq!(lines.find_line(0), None);
+}
+
+#[test]
+fn test_from_path() {
+    use smxdasm::errors::Error;
+    use smxdasm::file::SMXFile;
+
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    assert_eq!(p.borrow().find_function_name(18764).unwrap(), "OnPluginStart");
+
+    assert!(matches!(SMXFile::from_path("tests/missing.smx"), Err(Error::Io(_))));
+}
+
+#[test]
+fn test_function_name_index() {
+    let p = smxdasm::file::SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    let f = p.borrow();
+
+    let publics = f.publics.as_ref().unwrap().entries();
+    let called = f.called_functions.as_ref().unwrap().borrow().entries();
+
+    // Same answers as scanning publics, then called functions.
+    let linear = |addr: u32| -> Option<String> {
+        publics.iter().find(|p| p.address == addr).map(|p| p.name.clone())
+            .or_else(|| called.iter().find(|c| c.address == addr).map(|c| c.name.clone()))
+    };
+
+    let addrs = publics.iter().map(|p| p.address).chain(called.iter().map(|c| c.address));
+
+    for addr in addrs {
+        assert_eq!(f.find_function_name(addr as i32), linear(addr));
+        assert!(f.is_function_at_address(addr as i32));
+    }
+
+    // Inside OnPluginStart, not the start of a function.
+    assert_eq!(linear(18768), None);
+    assert_eq!(f.find_function_name(18768), None);
+    assert!(!f.is_function_at_address(18768));
+}
+
+#[test]
+fn test_data_read_cell() {
+    use smxdasm::errors::Error;
+
+    let p = smxdasm::file::SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    let f = p.borrow();
+
+    let data = f.data.as_ref().unwrap();
+
+    // __version, the first field of PlVers.
+    assert_eq!(data.read_cell(36).unwrap(), 5);
+    assert_eq!(data.read_float(36).unwrap().to_bits(), 5);
+
+    // NULL_VECTOR.
+    assert_eq!(data.read_float(52).unwrap(), 0.0);
+
+    let size = data.header().data_size;
+
+    assert!(data.read_cell(size - 4).is_ok());
+    assert!(matches!(data.read_cell(size - 2), Err(Error::OffsetOverflow)));
+    assert!(matches!(data.read_float(size), Err(Error::OffsetOverflow)));
+}
+
+#[test]
+fn test_data_string_at() {
+    use smxdasm::errors::Error;
+
+    let p = smxdasm::file::SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    let f = p.borrow();
+
+    let data = f.data.as_ref().unwrap();
+
+    // myinfo's fields point at the plugin's name and author literals.
+    let name = data.read_cell(38900).unwrap();
+    let author = data.read_cell(38908).unwrap();
+
+    assert_eq!(data.string_at(name as u32).unwrap(), "Source Chat Relay");
+    assert_eq!(data.string_at(author as u32).unwrap(), "Fishy");
+
+    assert!(matches!(data.string_at(data.header().data_size), Err(Error::InvalidIndex)));
+}
+
+#[test]
+fn test_plugin_info() {
+    use smxdasm::builder::SmxBuilder;
+
+    let p = smxdasm::file::SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    let info = p.borrow().plugin_info().unwrap();
+
+    assert_eq!(info.name, "Source Chat Relay");
+    assert_eq!(info.author, "Fishy");
+    assert_eq!(info.url, "https://keybase.io/RumbleFrog");
+
+    // No myinfo pubvar.
+    let p = smxdasm::file::SMXFile::new(SmxBuilder::new().data(&[0; 20], 20).pubvars(&[("__version", 0)]).build()).unwrap();
+
+    assert!(p.borrow().plugin_info().is_none());
 }
\ No newline at end of file
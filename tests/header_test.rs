@@ -1,11 +1,14 @@
 use std::fs::File;
 use std::io::Read;
+use std::collections::HashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 
 extern crate smxdasm;
 
 #[test]
 fn test_header() {
-    let mut file = File::open("F:\\Github\\smx-dasm-rs\\tests\\Source-Chat-Relay.smx").unwrap();
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
 
     let mut data = Vec::new();
 
@@ -14,4 +17,152 @@ fn test_header() {
     let d = smxdasm::headers::SMXHeader::new(data).unwrap();
 
     println!("{:?}", d);
+}
+
+#[test]
+fn test_section_entry_hash() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let d = smxdasm::headers::SMXHeader::new(data).unwrap();
+
+    let a = (*d.sections[0]).clone();
+    let b = (*d.sections[0]).clone();
+
+    let hash = |entry: &smxdasm::headers::SectionEntry| {
+        let mut hasher = DefaultHasher::new();
+        entry.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+
+    let mut set: HashSet<smxdasm::headers::SectionEntry> = d.sections.iter().map(|s| (**s).clone()).collect();
+
+    assert_eq!(set.len(), d.sections.len());
+
+    set.insert(a);
+
+    assert_eq!(set.len(), d.sections.len());
+}
+
+#[test]
+fn test_section_name_out_of_bounds() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+
+    let mut image = SmxBuilder::new().section(".test", vec![0; 4]).build();
+
+    assert!(smxdasm::headers::SMXHeader::new(image.clone()).is_ok());
+
+    // Point the first section's name far past the end of the image.
+    image[24..28].copy_from_slice(&0x7fff_0000i32.to_le_bytes());
+
+    assert!(matches!(smxdasm::headers::SMXHeader::new(image), Err(Error::OffsetOverflow)));
+}
+
+#[test]
+fn test_find_section() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let d = smxdasm::headers::SMXHeader::new(data).unwrap();
+
+    assert_eq!(d.find_section(".code").unwrap().name, ".code");
+    assert_eq!(d.find_section(".data").unwrap().name, ".data");
+    assert!(d.find_section(".bogus").is_none());
+
+    let by_name = d.sections_by_name();
+
+    assert_eq!(by_name.len(), d.sections.len());
+    assert_eq!(by_name[".code"], d.find_section(".code").unwrap());
+    assert!(!by_name.contains_key(".bogus"));
+}
+
+#[test]
+fn test_string_at() {
+    use smxdasm::errors::Error;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let d = smxdasm::headers::SMXHeader::new(data).unwrap();
+
+    let code = d.find_section(".code").unwrap();
+
+    assert_eq!(d.string_at(code.name_offset).unwrap(), ".code");
+
+    assert!(matches!(d.string_at(-1), Err(Error::InvalidIndex)));
+    assert!(matches!(d.string_at(d.data.len() as i32), Err(Error::InvalidIndex)));
+}
+
+#[test]
+fn test_check_version() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+    use smxdasm::headers::SMXHeader;
+
+    let image = |version: u16| SmxBuilder::new().version(version).build();
+    let header = |version: u16| SMXHeader::new_lenient(image(version)).unwrap();
+
+    assert!(header(SMXHeader::SP1_VERSION_1_0).check_version().is_ok());
+    assert!(header(SMXHeader::SP1_VERSION_1_7).check_version().is_ok());
+
+    // A newer minor revision, and a different product.
+    assert!(matches!(header(0x0108).check_version(), Err(Error::UnsupportedVersion(0x0108))));
+    assert!(matches!(header(0x0201).check_version(), Err(Error::UnsupportedVersion(0x0201))));
+
+    // SMXHeader::new checks the version itself.
+    assert!(SMXHeader::new(image(SMXHeader::SP1_VERSION_1_7)).is_ok());
+    assert!(matches!(SMXHeader::new(image(0x0108)), Err(Error::UnsupportedVersion(0x0108))));
+    assert!(matches!(SMXHeader::new(image(0x0201)), Err(Error::UnsupportedVersion(0x0201))));
+}
+
+#[test]
+fn test_from_reader() {
+    let d = smxdasm::headers::SMXHeader::from_reader(File::open("tests/Source-Chat-Relay.smx").unwrap()).unwrap();
+
+    assert!(d.find_section(".code").is_some());
+
+    let p = smxdasm::file::SMXFile::from_reader(File::open("tests/Source-Chat-Relay.smx").unwrap()).unwrap();
+
+    assert_eq!(p.borrow().header.sections.len(), d.sections.len());
+    assert!(p.borrow().instructions_at(18764).is_some());
+}
+
+#[test]
+fn test_section_out_of_bounds() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+
+    let mut image = SmxBuilder::new().section(".test", vec![0; 4]).build();
+
+    // Grow the first section past the end of the image.
+    image[32..36].copy_from_slice(&0x1000i32.to_le_bytes());
+
+    assert!(matches!(smxdasm::headers::SMXHeader::new(&image), Err(Error::SizeOverflow)));
+    assert!(matches!(smxdasm::file::SMXFile::new(&image), Err(Error::SizeOverflow)));
+}
+
+#[test]
+fn test_image_size_out_of_bounds() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+
+    let mut image = SmxBuilder::new().section(".test", vec![0; 4]).build();
+
+    // Claim a far larger image than was written.
+    image[11..15].copy_from_slice(&i32::MAX.to_le_bytes());
+
+    assert!(matches!(smxdasm::headers::SMXHeader::new(&image), Err(Error::SizeOverflow)));
 }
\ No newline at end of file
@@ -0,0 +1,339 @@
+use std::fs::File;
+use std::io::Read;
+use std::rc::Rc;
+
+extern crate smxdasm;
+
+use smxdasm::v1disassembler::V1Disassembler;
+use smxdasm::v1listing::{function_listing, DisassemblyOptions, Radix};
+use smxdasm::v1opcodes::V1OPCode;
+
+#[test]
+fn test_listing_source_lines() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let (data, code, addr) = {
+        let f = p.borrow();
+        let entry = f.publics.as_ref().unwrap().entries().into_iter().find(|e| e.name == "OnPluginStart").unwrap();
+
+        (f.header.data.clone(), Rc::clone(f.codev1.as_ref().unwrap()), entry.address as i32)
+    };
+
+    let insns = V1Disassembler::diassemble(Rc::clone(&p), data, code, addr).unwrap();
+
+    let options = DisassemblyOptions {
+        annotate_symbols: false,
+        jump_labels: false,
+        ..Default::default()
+    };
+
+    let listing = function_listing(&p.borrow(), &insns, &options);
+
+    assert_eq!(listing.lines().count(), insns.len());
+    assert!(listing.contains("Source-Chat-Relay.sp:"));
+
+    // Consecutive instructions on the same line are annotated only once.
+    let annotated = listing.lines().filter(|l| l.contains("; ")).count();
+
+    assert!(annotated > 0 && annotated < insns.len());
+
+    let annotated = function_listing(&p.borrow(), &insns, &DisassemblyOptions::default());
+
+    assert!(annotated.contains("; g_cHost"));
+}
+
+fn assemble(cells: &[i32]) -> Vec<u8> {
+    cells.iter().flat_map(|c| c.to_le_bytes().to_vec()).collect()
+}
+
+#[test]
+fn test_listing_options() {
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::LOAD_S_PRI as i32, -12,
+        V1OPCode::CONST_ALT as i32, 26,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let file = smxdasm::file::SMXFile::default();
+
+    let hex = function_listing(&file, &insns, &DisassemblyOptions::default());
+
+    assert!(hex.contains("load.s.pri -0xc"));
+    assert!(hex.contains("const.alt 0x1a"));
+
+    let decimal = function_listing(&file, &insns, &DisassemblyOptions {
+        radix: Radix::Decimal,
+        cell_offsets: true,
+        ..Default::default()
+    });
+
+    assert!(decimal.contains("load.s.pri -3"));
+    assert!(decimal.contains("const.alt 26"));
+}
+
+#[test]
+fn test_listing_float_constants() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    // NULL_VECTOR (0x34) is a float[3], g_iFlag (0x96f0) an int.
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::CONST as i32, 0x34, 0x3fc0_0000,
+        V1OPCode::CONST_PRI as i32, 0x4000_0000,
+        V1OPCode::STOR_PRI as i32, 0x34,
+        V1OPCode::CONST_PRI as i32, 0x4000_0000,
+        V1OPCode::STOR_PRI as i32, 0x96f0,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let options = DisassemblyOptions {
+        float_constants: true,
+        annotate_symbols: false,
+        annotate_lines: false,
+        ..Default::default()
+    };
+
+    let listing = function_listing(&p.borrow(), &insns, &options);
+    let lines: Vec<&str> = listing.lines().collect();
+
+    assert!(lines[0].ends_with("const 0x34 1.5f"));
+    assert!(lines[1].ends_with("const.pri 2.0f"));
+    assert!(lines[3].ends_with("const.pri 0x40000000"));
+
+    // Off by default.
+    let plain = function_listing(&p.borrow(), &insns, &DisassemblyOptions::default());
+
+    assert!(plain.contains("const 0x34 0x3fc00000"));
+}
+
+#[test]
+fn test_disassemble_listing() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let listing = f.disassemble_listing(18764).unwrap();
+
+    let lines: Vec<&str> = listing.lines().collect();
+
+    assert_eq!(lines.len(), f.functions[&18764].len() + 1);
+    assert!(lines[0].starts_with("0x0000494c +0x0000: proc"));
+    assert!(lines[1].starts_with("0x00004950 +0x0004: "));
+    assert!(lines.last().unwrap().contains(": retn"));
+
+    assert!(f.disassemble_listing(18768).is_err());
+}
+
+#[test]
+fn test_listing_jump_labels() {
+    // A loop: the backward JZER at 0x10 returns to the header at 0x4.
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::INC_PRI as i32,
+        V1OPCode::EQ_C_PRI as i32, 10,
+        V1OPCode::JZER as i32, 4,
+        V1OPCode::JUMP as i32, 32,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    assert_eq!(smxdasm::v1listing::jump_targets(&insns), vec![4, 32]);
+
+    let file = smxdasm::file::SMXFile::default();
+
+    // Labels are on by default.
+    let listing = function_listing(&file, &insns, &DisassemblyOptions::default());
+
+    let lines: Vec<&str> = listing.lines().collect();
+
+    assert_eq!(lines[0], "L_0x4:");
+    assert!(lines[1].starts_with("0x00000004: inc.pri"));
+    assert!(lines.contains(&"0x00000010: jzer L_0x4"));
+    assert!(lines.contains(&"0x00000018: jump L_0x20"));
+    assert_eq!(lines[lines.len() - 2], "L_0x20:");
+
+    // Without labels the raw targets are printed.
+    let raw = function_listing(&file, &insns, &DisassemblyOptions::new().jump_labels(false));
+
+    assert!(raw.contains("jzer 0x4"));
+    assert!(!raw.contains("L_0x4"));
+}
+
+#[test]
+fn test_listing_native_names() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let index = f.natives.as_ref().unwrap().entries().iter().position(|n| n.name == "PrintToServer").unwrap() as i32;
+
+    let (addr, insns) = f.functions.iter().find(|(_, insns)| {
+        insns.iter().any(|insn| insn.info.opcode == V1OPCode::SYSREQ_N && insn.params[0] == index)
+    }).unwrap();
+
+    let options = DisassemblyOptions {
+        annotate_lines: false,
+        resolve_names: true,
+        ..Default::default()
+    };
+
+    let listing = function_listing(&f, insns, &options);
+
+    assert!(listing.contains("sysreq.n PrintToServer "));
+
+    // Names are resolved by default, including in SMXFile's own listings.
+    assert!(function_listing(&f, insns, &DisassemblyOptions::default()).contains("sysreq.n PrintToServer "));
+    assert!(f.disassemble_listing(*addr).unwrap().contains("sysreq.n PrintToServer "));
+
+    let options = smxdasm::v1listing::DisasmOptions {
+        resolve_names: false,
+        ..Default::default()
+    };
+
+    let raw = function_listing(&f, insns, &options);
+
+    assert!(raw.contains(&format!("sysreq.n {:#x} ", index)));
+    assert!(!raw.contains("PrintToServer"));
+}
+
+#[test]
+fn test_listing_function_names() {
+    use smxdasm::builder::SmxBuilder;
+
+    let proc = V1OPCode::PROC as i32;
+    let call = V1OPCode::CALL as i32;
+    let retn = V1OPCode::RETN as i32;
+
+    // OnPluginStart calls the public Helper and an unnamed function at 0x20.
+    let data = SmxBuilder::new()
+        .code(&[
+            proc, call, 24, call, 32, retn,
+            proc, retn,
+            proc, retn,
+        ])
+        .publics(&[("OnPluginStart", 0), ("Helper", 24)])
+        .build();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let options = DisassemblyOptions {
+        resolve_names: true,
+        ..Default::default()
+    };
+
+    let listing = function_listing(&f, &f.functions[&0], &options);
+
+    assert!(listing.contains("call Helper"));
+    assert!(listing.contains("call sub_20"));
+}
+
+#[test]
+fn test_format_float() {
+    use smxdasm::v1listing::format_float;
+
+    assert_eq!(format_float(0x3fc0_0000), "1.5f");
+    assert_eq!(format_float(0x4000_0000), "2.0f");
+    assert_eq!(format_float(0xbf80_0000u32 as i32), "-1.0f");
+    assert_eq!(format_float(0), "0.0f");
+}
+
+#[test]
+fn test_listing_options_builder() {
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::CONST_ALT as i32, 26,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let file = smxdasm::file::SMXFile::default();
+
+    let options = DisassemblyOptions::new();
+
+    assert!(function_listing(&file, &insns, &options).contains("const.alt 0x1a"));
+
+    let options = options.radix(Radix::Decimal);
+
+    assert!(function_listing(&file, &insns, &options).contains("const.alt 26"));
+
+    let options = options.radix(Radix::Hex);
+
+    assert!(function_listing(&file, &insns, &options).contains("const.alt 0x1a"));
+}
+
+#[test]
+fn test_listing_source_line_toggle() {
+    let p = smxdasm::file::SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    let insns = p.borrow().disassemble_function_by_name("OnPluginStart").unwrap();
+
+    let options = DisassemblyOptions::new().annotate_symbols(false);
+
+    let listing = function_listing(&p.borrow(), &insns, &options);
+
+    assert!(listing.lines().next().unwrap().ends_with("\\Source-Chat-Relay.sp:334"));
+
+    let listing = function_listing(&p.borrow(), &insns, &options.annotate_lines(false));
+
+    assert!(!listing.contains(".sp:"));
+}
+
+#[test]
+fn test_listing_string_literals() {
+    let p = smxdasm::file::SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+
+    // myinfo's name (0x972c) and __version, a cell holding 5 (0x24).
+    let code = assemble(&[
+        V1OPCode::PROC as i32,
+        V1OPCode::CONST_PRI as i32, 0x972c,
+        V1OPCode::CONST_ALT as i32, 0x24,
+        V1OPCode::RETN as i32,
+    ]);
+
+    let insns = V1Disassembler::disassemble_bytes(&code, 0).unwrap();
+
+    let options = DisassemblyOptions::new().annotate_symbols(false).annotate_lines(false);
+
+    let listing = function_listing(&p.borrow(), &insns, &options);
+
+    assert!(listing.contains("const.pri 0x972c"));
+    assert!(listing.contains("; \"Source Chat Relay\""));
+    assert_eq!(listing.matches("; ").count(), 1);
+
+    let listing = function_listing(&p.borrow(), &insns, &options.string_literals(false));
+
+    assert!(!listing.contains("Source Chat Relay"));
+}
\ No newline at end of file
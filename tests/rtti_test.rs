@@ -0,0 +1,690 @@
+extern crate smxdasm;
+
+use smxdasm::rtti::CB;
+
+// Little-endian base 128, as emitted by the compiler.
+fn encode_u32(mut value: u32) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    loop {
+        let b = (value & 0x7f) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            bytes.push(b);
+            break;
+        }
+
+        bytes.push(b | 0x80);
+    }
+
+    bytes
+}
+
+#[test]
+fn test_decode_u32() {
+    for value in &[0u32, 127, 128, 16384, 0x0fff_ffff] {
+        let bytes = encode_u32(*value);
+
+        let mut offset: i32 = 0;
+
+        assert_eq!(CB::decode_u32(&bytes, &mut offset).unwrap(), *value as i32);
+        assert_eq!(offset as usize, bytes.len());
+    }
+}
+
+#[test]
+fn test_inline_bytes() {
+    use smxdasm::rtti::SMXRTTIData;
+
+    // An inline "int[]": ARRAY followed by INT32.
+    let type_id: i32 = ((((CB::INT32 as i32) << 8) | CB::ARRAY as i32) << 4) | CB::TYPEID_INLINE as i32;
+
+    assert_eq!(SMXRTTIData::inline_bytes(type_id), Some([CB::ARRAY, CB::INT32, 0, 0]));
+
+    // Complex typeids point into rtti.data instead.
+    assert_eq!(SMXRTTIData::inline_bytes((12 << 4) | CB::TYPEID_COMPLEX as i32), None);
+}
+
+#[test]
+fn test_decode_u32_truncated() {
+    use smxdasm::errors::Error;
+
+    // The continuation bit asks for a byte that isn't there.
+    let mut offset: i32 = 0;
+
+    assert!(matches!(CB::decode_u32([0x80], &mut offset), Err(Error::OffsetOverflow)));
+
+    let mut offset: i32 = 4;
+
+    assert!(matches!(CB::decode_u32([0x01], &mut offset), Err(Error::OffsetOverflow)));
+}
+
+#[test]
+fn test_type_decoding_fuzz() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::SMXFile;
+
+    // Small deterministic LCG so failures are reproducible.
+    let mut seed: u32 = 0x1234_5678;
+    let mut next = move || {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+        seed >> 8
+    };
+
+    for _ in 0..2000 {
+        let len = (next() % 16) as usize;
+
+        // Bias towards type codes so decoding gets past the first byte.
+        let codes = [CB::CONST, CB::FIXEDARRAY, CB::ARRAY, CB::FUNCTION, CB::ENUM, CB::TYPEDEF, CB::VARIADIC, CB::VOID, CB::BYREF, CB::INT32, 0x80, 0xff];
+        let bytes: Vec<u8> = (0..len).map(|_| match next() % 2 {
+            0 => codes[next() as usize % codes.len()],
+            _ => next() as u8,
+        }).collect();
+
+        let p = SMXFile::new(SmxBuilder::new().section("rtti.data", bytes).build()).unwrap();
+        let f = p.borrow();
+        let rtti = f.rtti_data.as_ref().unwrap();
+
+        let _ = rtti.function_type_from_offset(0);
+        let _ = rtti.typeset_types_from_offset(0);
+        let _ = rtti.type_from_id(next() as i32);
+        let _ = rtti.type_from_id(((next() % 16) << 4) as i32 | CB::TYPEID_COMPLEX as i32);
+    }
+}
+
+#[test]
+fn test_type_from_id_errors() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+    use smxdasm::file::SMXFile;
+
+    let p = SMXFile::new(SmxBuilder::new().section("rtti.data", vec![CB::FLOAT32]).build()).unwrap();
+    let f = p.borrow();
+    let rtti = f.rtti_data.as_ref().unwrap();
+
+    assert_eq!(rtti.type_from_id(((CB::INT32 as i32) << 4) | CB::TYPEID_INLINE as i32).unwrap(), "int");
+    assert_eq!(rtti.type_from_id(CB::TYPEID_COMPLEX as i32).unwrap(), "float");
+
+    assert!(matches!(rtti.type_from_id(0x5), Err(Error::UnknownTypeCode(0x5))));
+    assert!(matches!(rtti.type_from_id(0x7f << 4), Err(Error::UnknownTypeCode(0x7f))));
+}
+
+#[test]
+fn test_method_signatures() {
+    use std::fs::File;
+    use std::io::Read;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let methods = f.rtti_methods.as_ref().unwrap();
+
+    let close = methods.methods_ref().iter().find(|m| m.name == "ByteBuffer.Close").unwrap();
+
+    assert_eq!(f.method_signature(close).unwrap(), "function void (const ByteBuffer)");
+
+    let signatures = methods.resolved_signature(f.rtti_data.as_ref().unwrap()).unwrap();
+
+    assert_eq!(signatures.len(), methods.methods_ref().len());
+    assert!(signatures.contains(&("ByteBuffer.ReadString".to_string(), "function int (const ByteBuffer, char[], int)".to_string())));
+    assert!(signatures.contains(&("OnPluginStart".to_string(), "function void ()".to_string())));
+}
+
+#[test]
+fn test_native_arg_count() {
+    use std::fs::File;
+    use std::io::Read;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    // MarkNativeAsOptional(const char[])
+    assert_eq!(f.native_arg_count(0).unwrap(), Some(1));
+
+    // Format(char[], int, const char[], any...)
+    assert_eq!(f.native_arg_count(7).unwrap(), Some(4));
+
+    let format = f.rtti_natives.as_ref().unwrap().natives().into_iter().find(|n| n.name == "Format").unwrap();
+    let signature = f.rtti_data.as_ref().unwrap().function_from_offset(format.signature).unwrap();
+
+    assert!(signature.variadic);
+    assert_eq!(signature.return_type, "int");
+    assert_eq!(signature.to_string(), "function int (char[], int, const char[], any...)");
+
+    assert!(f.native_arg_count(1000).is_err());
+}
+
+#[test]
+fn test_native_signature() {
+    use std::fs::File;
+    use std::io::Read;
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+    use smxdasm::file::SMXFile;
+    use smxdasm::rtti::RTTINative;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let natives = f.rtti_natives.as_ref().unwrap().natives();
+
+    assert_eq!(f.native_signature(&natives[0]).unwrap(), "function void (const char[])");
+
+    // Without rtti.data there is nothing to decode against.
+    let empty = SMXFile::new(SmxBuilder::new().build()).unwrap();
+
+    let native = RTTINative {
+        name: "Format".to_string(),
+        signature: 0,
+    };
+
+    assert!(matches!(empty.borrow().native_signature(&native), Err(Error::MissingSection(_))));
+}
+
+#[test]
+fn test_fields_with_types() {
+    use std::fs::File;
+    use std::io::Read;
+    use smxdasm::file::SMXFile;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let fields = f.rtti_fields.as_ref().unwrap().fields_with_types(&f).unwrap();
+
+    assert_eq!(fields.len(), 13);
+    assert_eq!(fields[0], ("name".to_string(), "const char[]".to_string()));
+    assert_eq!(fields[7], ("autoload".to_string(), "bool".to_string()));
+    assert_eq!(fields[9], ("version".to_string(), "int".to_string()));
+
+    let field = &f.rtti_fields.as_ref().unwrap().fields()[9];
+
+    assert_eq!(f.type_name(field.type_id).unwrap(), "int");
+}
+
+#[test]
+fn test_is_variadic() {
+    use std::fs::File;
+    use std::io::Read;
+    use smxdasm::file::SMXFile;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let natives = f.rtti_natives.as_ref().unwrap().natives();
+
+    let format = natives.iter().find(|n| n.name == "Format").unwrap();
+
+    assert!(format.is_variadic(&f).unwrap());
+    assert!(f.is_variadic(format.signature).unwrap());
+
+    let optional = natives.iter().find(|n| n.name == "MarkNativeAsOptional").unwrap();
+
+    assert!(!optional.is_variadic(&f).unwrap());
+
+    let methods = f.rtti_methods.as_ref().unwrap().methods();
+
+    let close = methods.iter().find(|m| m.name == "ByteBuffer.Close").unwrap();
+
+    assert!(!close.is_variadic(&f).unwrap());
+}
+
+#[test]
+fn test_enum_struct_fields_for() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::SMXFile;
+
+    // rtti list tables start with header_size, row_size and row_count.
+    fn table(row_size: u32, rows: &[[i32; 3]]) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+
+        data.extend(&12u32.to_le_bytes());
+        data.extend(&row_size.to_le_bytes());
+        data.extend(&(rows.len() as u32).to_le_bytes());
+
+        for row in rows {
+            for cell in row {
+                data.extend(&cell.to_le_bytes());
+            }
+        }
+
+        data
+    }
+
+    let mut builder = SmxBuilder::new();
+
+    let point = builder.add_name("Point");
+    let empty = builder.add_name("Empty");
+    let rect = builder.add_name("Rect");
+    let x = builder.add_name("x");
+    let y = builder.add_name("y");
+    let w = builder.add_name("w");
+
+    // Point has x and y, Empty has none, Rect has x, y and w.
+    let structs = table(12, &[[point, 0, 2], [empty, 2, 0], [rect, 2, 3]]);
+    let fields = table(12, &[[x, 0, 0], [y, 0, 1], [x, 0, 0], [y, 0, 1], [w, 0, 2]]);
+
+    let image = builder
+        .section("rtti.enumstructs", structs)
+        .section("rtti.enumstruct_fields", fields)
+        .build();
+
+    let p = SMXFile::new(image).unwrap();
+
+    let f = p.borrow();
+
+    let structs = f.rtti_enum_structs.as_ref().unwrap();
+    let fields = f.rtti_enum_struct_fields.as_ref().unwrap();
+
+    let names = |index: usize| -> Vec<String> {
+        structs.fields_for(index, fields).iter().map(|field| field.name.clone()).collect()
+    };
+
+    assert_eq!(names(0), vec!["x", "y"]);
+    assert!(names(1).is_empty());
+    assert_eq!(names(2), vec!["x", "y", "w"]);
+    assert!(names(3).is_empty());
+}
+
+#[test]
+fn test_classdef_fields_for() {
+    use std::fs::File;
+    use std::io::Read;
+    use smxdasm::file::SMXFile;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let classdefs = f.rtti_classdefs.as_ref().unwrap();
+    let fields = f.rtti_fields.as_ref().unwrap();
+
+    let layout: Vec<(String, Vec<String>)> = classdefs.defs().iter().enumerate().map(|(i, def)| {
+        (def.name.clone(), classdefs.fields_for(i, fields).iter().map(|field| field.name.clone()).collect())
+    }).collect();
+
+    assert_eq!(layout.len(), 3);
+    assert_eq!(layout[0], ("Plugin".to_string(), vec!["name", "description", "author", "version", "url"].into_iter().map(String::from).collect()));
+    assert_eq!(layout[1], ("Extension".to_string(), vec!["name", "file", "autoload", "required"].into_iter().map(String::from).collect()));
+    assert_eq!(layout[2], ("PlVers".to_string(), vec!["version", "filevers", "date", "time"].into_iter().map(String::from).collect()));
+
+    assert!(classdefs.fields_for(3, fields).is_empty());
+}
+
+#[test]
+fn test_function_qualifiers() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::SMXFile;
+
+    let decode = |bytes: Vec<u8>| -> String {
+        let p = SMXFile::new(SmxBuilder::new().section("rtti.data", bytes).build()).unwrap();
+        let f = p.borrow();
+
+        let signature = f.rtti_data.as_ref().unwrap().function_type_from_offset(0).unwrap();
+
+        signature
+    };
+
+    // const int& in both marker orders.
+    assert_eq!(decode(vec![1, CB::VOID, CB::BYREF, CB::CONST, CB::INT32]), "function void (const int&)");
+    assert_eq!(decode(vec![1, CB::VOID, CB::CONST, CB::BYREF, CB::INT32]), "function void (const int&)");
+
+    assert_eq!(decode(vec![1, CB::VOID, CB::CONST, CB::ARRAY, CB::CHAR8]), "function void (const char[])");
+
+    // const return types, including a const on void.
+    assert_eq!(decode(vec![0, CB::CONST, CB::ARRAY, CB::CHAR8]), "function const char[] ()");
+    assert_eq!(decode(vec![0, CB::CONST, CB::VOID]), "function void ()");
+
+    assert_eq!(decode(vec![2, CB::INT32, CB::BYREF, CB::FLOAT32, CB::CONST, CB::ANY]), "function int (float&, const any)");
+}
+
+#[test]
+fn test_type_info() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::SMXFile;
+    use smxdasm::rtti::TypeInfo;
+
+    let mut builder = SmxBuilder::new();
+
+    let color = builder.add_name("Color");
+
+    // rtti.enums: header_size, row_size, row_count, then name and three
+    // reserved cells per row.
+    let mut enums: Vec<u8> = Vec::new();
+
+    for cell in &[12, 16, 1, color, 0, 0, 0] {
+        enums.extend(&cell.to_le_bytes());
+    }
+
+    // A complex "const Color[]" at offset 0.
+    let image = builder
+        .section("rtti.data", vec![CB::CONST, CB::ARRAY, CB::ENUM, 0])
+        .section("rtti.enums", enums)
+        .build();
+
+    let p = SMXFile::new(image).unwrap();
+    let f = p.borrow();
+    let rtti = f.rtti_data.as_ref().unwrap();
+
+    let inline = |bytes: [u8; 3]| -> i32 {
+        (((bytes[2] as i32) << 16 | (bytes[1] as i32) << 8 | bytes[0] as i32) << 4) | CB::TYPEID_INLINE as i32
+    };
+
+    assert_eq!(rtti.type_info(inline([CB::ENUM, 0, 0])).unwrap(), TypeInfo {
+        name: "Color".into(),
+        is_const: false,
+        kind: CB::ENUM,
+    });
+
+    assert_eq!(rtti.type_info(inline([CB::CONST, CB::ENUM, 0])).unwrap(), TypeInfo {
+        name: "Color".into(),
+        is_const: true,
+        kind: CB::ENUM,
+    });
+
+    assert_eq!(rtti.type_from_id(inline([CB::CONST, CB::ENUM, 0])).unwrap(), "const Color");
+
+    assert_eq!(rtti.type_info(CB::TYPEID_COMPLEX as i32).unwrap(), TypeInfo {
+        name: "Color[]".into(),
+        is_const: true,
+        kind: CB::ARRAY,
+    });
+}
+
+#[test]
+fn test_type_name_cache() {
+    use std::fs::File;
+    use std::io::Read;
+    use std::rc::Rc;
+    use std::time::Instant;
+    use smxdasm::file::SMXFile;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let rtti = Rc::clone(p.borrow().rtti_data.as_ref().unwrap());
+    let fields = p.borrow().rtti_fields.as_ref().unwrap().fields();
+    let methods = p.borrow().rtti_methods.as_ref().unwrap().methods();
+
+    let first: Vec<String> = fields.iter().map(|field| rtti.type_from_id(field.type_id).unwrap()).collect();
+    let signatures: Vec<String> = methods.iter().map(|method| rtti.function_type_from_offset(method.signature).unwrap()).collect();
+
+    let start = Instant::now();
+
+    for _ in 0..1000 {
+        for (field, name) in fields.iter().zip(&first) {
+            assert_eq!(&rtti.type_from_id(field.type_id).unwrap(), name);
+        }
+    }
+
+    println!("{} cached lookups in {:?}", 1000 * fields.len(), start.elapsed());
+
+    // With the tables gone, a reparse would fail with MissingSection; cached
+    // lookups still answer.
+    {
+        let mut f = p.borrow_mut();
+
+        f.rtti_enums = None;
+        f.rtti_classdefs = None;
+        f.rtti_typedefs = None;
+        f.rtti_typesets = None;
+        f.rtti_enum_structs = None;
+    }
+
+    for (method, signature) in methods.iter().zip(&signatures) {
+        assert_eq!(&rtti.function_type_from_offset(method.signature).unwrap(), signature);
+    }
+}
+
+#[test]
+fn test_decode_snapshot() {
+    use std::fs::File;
+    use std::fmt::Write;
+    use std::io::Read;
+    use smxdasm::file::SMXFile;
+
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let rtti = f.rtti_data.as_ref().unwrap();
+
+    // Every RTTI type in the plugin, one per line, as decoded when the
+    // snapshot was taken.
+    let mut out = String::new();
+
+    for method in f.rtti_methods.as_ref().unwrap().methods() {
+        writeln!(out, "{}: {}", method.name, rtti.function_type_from_offset(method.signature).unwrap()).unwrap();
+    }
+
+    for native in f.rtti_natives.as_ref().unwrap().natives() {
+        writeln!(out, "{}: {}", native.name, rtti.function_type_from_offset(native.signature).unwrap()).unwrap();
+    }
+
+    for field in f.rtti_fields.as_ref().unwrap().fields() {
+        writeln!(out, "{}: {}", field.name, rtti.type_from_id(field.type_id).unwrap()).unwrap();
+    }
+
+    for typedef in f.rtti_typedefs.as_ref().unwrap().typedefs() {
+        writeln!(out, "{}: {}", typedef.name, rtti.type_from_id(typedef.type_id).unwrap()).unwrap();
+    }
+
+    for typeset in f.rtti_typesets.as_ref().unwrap().typesets() {
+        writeln!(out, "{}: {}", typeset.name, rtti.typeset_display_from_offset(typeset.signature).unwrap()).unwrap();
+    }
+
+    let mut expected = String::new();
+
+    File::open("tests/Source-Chat-Relay.rtti.txt").unwrap().read_to_string(&mut expected).unwrap();
+
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn test_expand_typedef() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::errors::Error;
+    use smxdasm::file::SMXFile;
+
+    let inline = |bytes: [u8; 3]| -> i32 {
+        (((bytes[2] as i32) << 16 | (bytes[1] as i32) << 8 | bytes[0] as i32) << 4) | CB::TYPEID_INLINE as i32
+    };
+
+    let mut builder = SmxBuilder::new();
+
+    let rows = [
+        ("Outer", inline([CB::TYPEDEF, 1, 0])),
+        ("Inner", inline([CB::INT32, 0, 0])),
+        ("Ping", inline([CB::TYPEDEF, 3, 0])),
+        ("Pong", inline([CB::TYPEDEF, 2, 0])),
+        ("Constant", inline([CB::CONST, CB::TYPEDEF, 0])),
+    ];
+
+    // rtti.typedefs: header_size, row_size, row_count, then name and type id
+    // per row.
+    let mut typedefs: Vec<u8> = Vec::new();
+
+    for cell in &[12, 8, rows.len() as i32] {
+        typedefs.extend(&cell.to_le_bytes());
+    }
+
+    for (name, type_id) in &rows {
+        typedefs.extend(&builder.add_name(name).to_le_bytes());
+        typedefs.extend(&type_id.to_le_bytes());
+    }
+
+    let image = builder
+        .section("rtti.data", vec![])
+        .section("rtti.typedefs", typedefs)
+        .build();
+
+    let p = SMXFile::new(image).unwrap();
+    let f = p.borrow();
+
+    assert_eq!(f.expand_typedef(1).unwrap(), "int");
+    assert_eq!(f.expand_typedef(0).unwrap(), "int");
+    assert_eq!(f.expand_typedef(4).unwrap(), "const int");
+
+    assert!(matches!(f.expand_typedef(2), Err(Error::Other(_))));
+    assert!(matches!(f.expand_typedef(5), Err(Error::InvalidIndex)));
+
+    // Decoding alone stops at the first name.
+    assert_eq!(f.type_name(rows[0].1).unwrap(), "Inner");
+}
+
+
+#[test]
+fn test_typeset_display() {
+    use smxdasm::builder::SmxBuilder;
+    use smxdasm::file::SMXFile;
+
+    // A two member typeset at offset 0.
+    let image = SmxBuilder::new()
+        .section("rtti.data", vec![2, CB::INT32, CB::CONST, CB::FLOAT32])
+        .build();
+
+    let p = SMXFile::new(image).unwrap();
+    let f = p.borrow();
+    let rtti = f.rtti_data.as_ref().unwrap();
+
+    assert_eq!(rtti.typeset_types_from_offset(0).unwrap(), vec!["int", "const float"]);
+    assert_eq!(rtti.typeset_display_from_offset(0).unwrap(), "int | const float");
+}
+
+#[test]
+fn test_classdef_flags() {
+    use smxdasm::file::SMXFile;
+    use smxdasm::rtti::{ClassDefFlags, RTTIClassDef};
+
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+    let f = p.borrow();
+
+    for def in f.rtti_classdefs.as_ref().unwrap().defs() {
+        assert_eq!(def.flag_set(), ClassDefFlags::empty());
+        assert_eq!(def.class_type(), RTTIClassDef::TYPE_STRUCT);
+    }
+
+    let fields = f.rtti_fields.as_ref().unwrap().fields();
+
+    // Plugin.name is a const char[]; Extension.autoload a plain bool.
+    assert_eq!(fields[0].name, "name");
+    assert!(f.is_const_field(&fields[0]).unwrap());
+
+    assert_eq!(fields[7].name, "autoload");
+    assert!(!f.is_const_field(&fields[7]).unwrap());
+}
+
+#[test]
+fn test_field_flags() {
+    use smxdasm::file::SMXFile;
+    use smxdasm::rtti::{FieldFlags, RTTIField};
+
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+    let f = p.borrow();
+
+    for field in f.rtti_fields.as_ref().unwrap().fields() {
+        assert_eq!(field.flag_set(), FieldFlags::empty());
+    }
+
+    // Undefined bits are dropped.
+    let field = RTTIField {
+        flags: 0x10,
+        name_offset: 0,
+        type_id: 0,
+        name: String::new(),
+    };
+
+    assert_eq!(field.flag_set(), FieldFlags::empty());
+    assert_eq!(FieldFlags::all(), FieldFlags::empty());
+}
+
+#[test]
+fn test_method_at() {
+    use smxdasm::file::SMXFile;
+
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+    let f = p.borrow();
+
+    let methods = f.rtti_methods.as_ref().unwrap();
+
+    // OnPluginStart spans [18764, 19896); OnConfigsExecuted follows it.
+    assert_eq!(methods.method_at(18764).unwrap().name, "OnPluginStart");
+    assert_eq!(methods.method_at(18800).unwrap().name, "OnPluginStart");
+    assert_eq!(methods.method_at(19896).unwrap().name, "OnConfigsExecuted");
+    assert!(methods.method_at(-4).is_none());
+
+    for method in methods.methods_ref() {
+        assert_eq!(methods.method_at(method.pcode_start).unwrap().name, method.name);
+        assert_eq!(methods.method_at(method.pcode_end - 4).unwrap().name, method.name);
+    }
+}
+
+#[test]
+fn test_public_signature() {
+    use smxdasm::file::SMXFile;
+
+    let p = SMXFile::from_path("tests/Source-Chat-Relay.smx").unwrap();
+    let f = p.borrow();
+
+    let publics = f.publics.as_ref().unwrap().entries();
+    let signature = |name: &str| f.public_signature(publics.iter().find(|e| e.name == name).unwrap());
+
+    assert_eq!(signature("OnPluginStart").unwrap(), "function void ()");
+    assert_eq!(signature("OnClientConnected").unwrap(), "function void (int)");
+
+    // Every public in the sample is covered by a method.
+    assert!(publics.iter().all(|e| f.public_signature(e).is_some()));
+}
\ No newline at end of file
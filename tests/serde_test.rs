@@ -0,0 +1,57 @@
+extern crate smxdasm;
+
+use std::fs::File;
+use std::io::Read;
+
+#[test]
+fn test_serialize_publics() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let publics = f.publics.as_ref().unwrap().entries();
+
+    let json = serde_json::to_value(&publics).unwrap();
+
+    let entries = json.as_array().unwrap();
+
+    assert_eq!(entries.len(), publics.len());
+
+    let start = entries.iter().find(|entry| entry["name"] == "OnPluginStart").unwrap();
+
+    assert_eq!(start["address"], 18764);
+}
+
+#[test]
+fn test_to_json() {
+    let mut file = File::open("tests/Source-Chat-Relay.smx").unwrap();
+
+    let mut data: Vec<u8> = Vec::new();
+
+    file.read_to_end(&mut data).unwrap();
+
+    let p = smxdasm::file::SMXFile::new(data).unwrap();
+
+    let f = p.borrow();
+
+    let doc: serde_json::Value = serde_json::from_str(&f.to_json().unwrap()).unwrap();
+
+    let publics = doc["publics"].as_array().unwrap();
+
+    assert_eq!(publics.len(), f.publics.as_ref().unwrap().entries_ref().len());
+    assert!(publics.iter().any(|entry| entry["name"] == "OnPluginStart"));
+
+    assert_eq!(doc["sections"].as_array().unwrap().len(), f.header.sections.len());
+    assert!(doc["rtti"]["methods"].is_array());
+
+    let start = doc["functions"].as_array().unwrap().iter().find(|fun| fun["address"] == 18764).unwrap();
+
+    assert_eq!(start["name"], "OnPluginStart");
+    assert_eq!(start["instructions"].as_array().unwrap().len(), f.functions[&18764].len());
+}
\ No newline at end of file